@@ -2,81 +2,285 @@
 use cosmwasm_std::entry_point;
 // use cosmwasm_std::CosmosMsg::{Bank};
 use cosmwasm_std::{
-    from_binary, to_binary, Addr, BankMsg, Binary, Coin, CosmosMsg, Deps, DepsMut, Env,
-    MessageInfo, Response, StdResult, Uint128};
+    from_binary, to_binary, Addr, Api, Binary, Coin, Deps, DepsMut, Env,
+    Decimal, MessageInfo, Order, Response, StdError, StdResult, Storage, Uint128, Uint256};
 
 use crate::error::ContractError;
 use crate::msg::{
-    EntropyCallbackData, ExecuteMsg, GameResponse, InstantiateMsg, MigrateMsg, QueryMsg,
+    ConfigResponse, EntropyCallbackData, ExecuteMsg, GameResponse, GamesResponse, InstantiateMsg,
+    BankrollResponse, DenomStats, DenomsResponse, PositionResponse, VaultResponse, MaxBetResponse, MigrateMsg, OddsResponse, OrderBy, OwnershipResponse, PauseStatusResponse,
+    AccruedFeesResponse, PlayerStatsResponse, QueryMsg, ReceiveMsg, SegmentOdds, StatsResponse, UpdateConfigMsg,
+};
+use crate::state::{
+    games, AccruedFee, AssetInfo, Config, DenomConfig, FeeModel, FeeRecipient, FEES, Game, GameStatus, HouseStats, PendingOwner, Wheel, CONFIG, DENOMS, IDX, PAUSED,
+    Bankroll, RateLimit, Unbonding, BANKROLL, SHARES, UNBONDING, PENDING_OWNER, PLAYER_ACTIVITY, PLAYER_STATS, RESERVED, STATS, WAGER_WINDOWS,
 };
-use crate::state::{Config, Game, RuleSet, CONFIG, GAME, IDX};
 
-use sha2::{Digest, Sha256};
-use cw_utils::one_coin;
+use num_bigint::BigUint;
+use num_traits::{One, ToPrimitive};
+use sha2::{Digest, Sha512};
+use cw_utils::{one_coin, Duration, Expiration};
 use entropy_beacon_cosmos::{CalculateFeeQuery, EntropyCallbackMsg, EntropyRequest};
+use cw2::{get_contract_version, set_contract_version};
+use cw20::Cw20ReceiveMsg;
+use cw_storage_plus::Bound;
 use kujira::denom::Denom;
-use cw2::set_contract_version;
 
 // Version info for migration
 const CONTRACT_NAME: &str = "crates.io:Spin-the-whale";
 const CONTRACT_VERSION: &str = env!("CARGO_PKG_VERSION");
 
-/// Our [`InstantiateMsg`] contains the address of the entropy beacon contract.
-/// We save this address in the contract state.
+/// Our [`InstantiateMsg`] contains the entropy beacon address, the owner, the default
+/// wheel, the fee recipients and the whitelisted bet denoms with their own limits and fees.
+/// Everything is validated before it is saved in the contract state.
 #[cfg_attr(not(feature = "library"), entry_point)]
 pub fn instantiate(
     deps: DepsMut,
     _env: Env,
     info: MessageInfo,
-    _msg: InstantiateMsg,
+    msg: InstantiateMsg,
 ) -> Result<Response, ContractError> {
     // Set contract version
     set_contract_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
 
-    // entropy beacon addr TESTNET harpoon-4
-    let entropy_beacon_addr = "kujira1xwz7fll64nnh4p9q8dyh9xfvqlwfppz4hqdn2uyq2fcmmqtnf5vsugyk7u"; 
+    // Validate the entropy beacon addr
+    let validated_entropy_beacon_addr = deps.api.addr_validate(&msg.entropy_beacon_addr)?;
 
-    // entropy beacon addr MAINNET kaiyo-1
-    // let entropy_beacon_addr = "kujira1x623ehq3gqx9m9t8asyd9cgehf32gy94mhsw8l99cj3l2nvda2fqrjwqy5"; 
+    // validate the owner's address
+    let validated_owner_address: Addr = deps.api.addr_validate(&msg.owner_addr)?;
 
-    // Validate the entropy beacon addr 
-    let validated_entropy_beacon_addr = deps.api.addr_validate(entropy_beacon_addr)?;
+    // validate the guardian's address, if there is one
+    let validated_guardian_address = msg
+        .guardian_addr
+        .map(|guardian| deps.api.addr_validate(&guardian))
+        .transpose()?;
 
-    // validate the owner's address
-    let validated_owner_address: Addr = deps.api.addr_validate(info.sender.as_ref())?;
+    // validate the fee recipients' addresses
+    let fee_recipients = validate_fee_recipients(deps.api, msg.fee_recipients)?;
 
     // Initialize Config
     let config = Config {
         entropy_beacon_addr: validated_entropy_beacon_addr,
-        owner_addr: validated_owner_address,
-        house_bankroll: Coin { // Init house bankroll to zero ukuji 
-            denom: "ukuji".to_string(),
-            amount: Uint128::zero(),
-        },
-        token: Denom::from("ukuji"), // Init token to ukuji
-        fee_amount: Uint128::zero(),
-        rule_set: RuleSet { // Payout ratios
-            zero: Uint128::from(1u128), // 1:1
-            one: Uint128::from(3u128), // 3:1
-            two: Uint128::from(5u128), // 5:1
-            three: Uint128::from(10u128), // 10:1
-            four: Uint128::from(20u128), // 20:1
-            five: Uint128::from(45u128), // 45:1
-            six: Uint128::from(45u128), // 45:1 
-        },
+        owner_addr: Some(validated_owner_address.clone()),
+        guardian_addr: validated_guardian_address,
+        wheel: msg.wheel,
+        min_house_edge: msg.min_house_edge,
+        max_exposure: msg.max_exposure,
+        beacon_fee_denom: msg.beacon_fee_denom,
+        fee_recipients,
+        rate_limit: msg.rate_limit,
+        entropy_timeout: msg.entropy_timeout,
+        callback_gas_limit: msg.callback_gas_limit,
+        unbonding_period: msg.unbonding_period,
     };
 
+    // Reject inconsistent configurations before anything is stored
+    validate_config(&config)?;
+
     // Save the initialized config to storage 
     CONFIG.save(deps.storage, &config)?;
     
     // Save the initialized game index 0 to storage
     IDX.save(deps.storage, &Uint128::zero())?;
 
-    Ok(Response::new()
+    // Start accepting games right away
+    PAUSED.save(deps.storage, &false)?;
+
+    // Whitelist the bet denoms, each starting out with an empty bankroll
+    for denom_config in &msg.denoms {
+        validate_denom_config(deps.api, &config, denom_config)?;
+        DENOMS.save(deps.storage, denom_config.denom.key(), denom_config)?;
+        BANKROLL.save(deps.storage, denom_config.denom.key(), &Bankroll::default())?;
+    }
+
+    // Funds sent along are the owner's first deposits into the bankrolls of their denoms,
+    // which have to be whitelisted for the funds to be tracked at all
+    let mut initial_bankroll = vec![];
+    for coin in &info.funds {
+        let denom = AssetInfo::Native(Denom::from(coin.denom.as_str()));
+        if !is_whitelisted(deps.storage, &denom)? {
+            return Err(ContractError::InvalidToken {});
+        }
+        if !coin.amount.is_zero() {
+            deposit_liquidity(deps.storage, &denom, &validated_owner_address, coin.amount)?;
+            initial_bankroll.push(coin.to_string());
+        }
+    }
+    let denoms = msg
+        .denoms
+        .iter()
+        .map(|denom_config| denom_config.denom.to_string())
+        .collect::<Vec<_>>();
+
+    let mut response = Response::new()
         .add_attribute("method", "instantiate")
-        .add_attribute("owner", config.owner_addr.to_string())
+        .add_attribute("owner", validated_owner_address.to_string())
         .add_attribute("entropy_beacon_addr", config.entropy_beacon_addr.to_string())
-        .add_attribute("house_bankroll", config.house_bankroll.to_string()))
+        .add_attribute("min_house_edge", config.min_house_edge.to_string())
+        .add_attribute("max_exposure", config.max_exposure.to_string())
+        .add_attribute("beacon_fee_denom", config.beacon_fee_denom.to_string())
+        .add_attribute("entropy_timeout", config.entropy_timeout.to_string())
+        .add_attribute("callback_gas_limit", config.callback_gas_limit.to_string())
+        .add_attribute("unbonding_period", config.unbonding_period.to_string());
+
+    // Attribute values must not be empty, a contract can start out without denoms,
+    // which are added later, or without a bankroll
+    if !denoms.is_empty() {
+        response = response.add_attribute("denoms", denoms.join(","));
+    }
+    if !initial_bankroll.is_empty() {
+        response = response.add_attribute("house_bankroll", initial_bankroll.join(","));
+    }
+
+    Ok(response)
+}
+
+// Check that a wheel can be spun and every segment is worth betting on
+pub fn validate_wheel(wheel: &Wheel) -> Result<(), ContractError> {
+    if wheel.segments.is_empty() || wheel.segments.len() > Wheel::MAX_SEGMENTS {
+        return Err(ContractError::InvalidWheel {
+            reason: format!("must have between 1 and {} segments", Wheel::MAX_SEGMENTS),
+        });
+    }
+
+    for (number, segment) in wheel.segments.iter().enumerate() {
+        if segment.label.is_empty() {
+            return Err(ContractError::InvalidWheel {
+                reason: format!("segment {number} has no label"),
+            });
+        }
+        if segment.weight == 0 {
+            return Err(ContractError::InvalidWheel {
+                reason: format!("segment {number} must take up at least one slot"),
+            });
+        }
+        if segment.payout.is_zero() {
+            return Err(ContractError::InvalidWheel {
+                reason: format!("segment {number} must pay out something"),
+            });
+        }
+    }
+
+    Ok(())
+}
+
+// Check that the best bet on a wheel still leaves the house its edge
+fn validate_house_edge(wheel: &Wheel, min_house_edge: Decimal) -> Result<(), ContractError> {
+    let rtp = wheel.rtp().map_err(StdError::from)?;
+    if rtp.checked_add(min_house_edge).map_err(StdError::from)? > Decimal::one() {
+        return Err(ContractError::HouseEdgeTooLow { rtp, min_house_edge });
+    }
+    Ok(())
+}
+
+// Check that a config is internally consistent
+pub fn validate_config(config: &Config) -> Result<(), ContractError> {
+    validate_wheel(&config.wheel)?;
+    validate_house_edge(&config.wheel, config.min_house_edge)?;
+
+    // The entropy has to be paid for in some denom
+    if config.beacon_fee_denom.to_string().is_empty() {
+        return Err(ContractError::InvalidDenom {});
+    }
+
+    // A single bet may take up some, but never more than all, of the bankroll
+    if config.max_exposure.is_zero() || config.max_exposure > Decimal::one() {
+        return Err(ContractError::InvalidMaxExposure {});
+    }
+
+    // A cooldown that is switched on must let something through
+    if config.rate_limit.cooldown_blocks == Some(0) {
+        return Err(ContractError::InvalidRateLimit {});
+    }
+
+    // Games must be given some time to receive their entropy
+    if matches!(config.entropy_timeout, Duration::Height(0) | Duration::Time(0)) {
+        return Err(ContractError::InvalidTimeout {});
+    }
+
+    // The beacon needs some gas to call us back
+    if config.callback_gas_limit == 0 {
+        return Err(ContractError::InvalidCallbackGasLimit {});
+    }
+
+    Ok(())
+}
+
+// Check that the terms of a bet denom are consistent, and that its own wheel
+// meets the house edge of the config
+pub fn validate_denom_config(
+    api: &dyn Api,
+    config: &Config,
+    denom_config: &DenomConfig,
+) -> Result<(), ContractError> {
+    // A coin denom must be set, a token must be a contract address
+    match &denom_config.denom {
+        AssetInfo::Native(denom) if denom.to_string().is_empty() => {
+            return Err(ContractError::InvalidDenom {})
+        }
+        AssetInfo::Native(_) => {}
+        AssetInfo::Cw20(contract) => {
+            api.addr_validate(contract.as_str())?;
+        }
+    }
+
+    if let Some(wheel) = &denom_config.wheel {
+        validate_wheel(wheel)?;
+        validate_house_edge(wheel, config.min_house_edge)?;
+    }
+
+    // Bet limits must describe a non-empty range
+    if denom_config.min_bet.is_zero() || denom_config.min_bet > denom_config.max_bet {
+        return Err(ContractError::InvalidBetLimits {
+            min_bet: denom_config.min_bet,
+            max_bet: denom_config.max_bet,
+        });
+    }
+
+    // A wager cap that is switched on must let something through
+    if denom_config
+        .wager_cap
+        .as_ref()
+        .is_some_and(|cap| cap.window_blocks == 0 || cap.amount.is_zero())
+    {
+        return Err(ContractError::InvalidRateLimit {});
+    }
+
+    // The fee must not swallow the smallest possible bet, or a whole stake or win
+    match denom_config.fee {
+        FeeModel::Flat(fee_amount) if fee_amount >= denom_config.min_bet => {
+            return Err(ContractError::InvalidFee {
+                fee_amount,
+                min_bet: denom_config.min_bet,
+            })
+        }
+        FeeModel::WagerBps(bps) | FeeModel::NetWinningsBps(bps) if bps >= FeeModel::MAX_BPS => {
+            return Err(ContractError::InvalidFeeBps { bps })
+        }
+        _ => {}
+    }
+
+    Ok(())
+}
+
+// Check that fees can be split between the recipients, and validate their addresses
+pub fn validate_fee_recipients(
+    api: &dyn Api,
+    recipients: Vec<FeeRecipient>,
+) -> Result<Vec<FeeRecipient>, ContractError> {
+    if recipients.is_empty() || recipients.iter().any(|recipient| recipient.weight == 0) {
+        return Err(ContractError::InvalidFeeRecipients {});
+    }
+
+    recipients
+        .into_iter()
+        .map(|recipient| {
+            Ok(FeeRecipient {
+                address: api.addr_validate(recipient.address.as_str())?,
+                weight: recipient.weight,
+            })
+        })
+        .collect()
 }
 
 #[cfg_attr(not(feature = "library"), entry_point)]
@@ -90,208 +294,861 @@ pub fn execute(
         // #STEP 1:
         // Validate player's bet amount and number
         // and handle requesting entropy from the beacon.
-        ExecuteMsg::Pull { bet_number } => execute_entropy_beacon_pull(deps, env, info, bet_number),
+        ExecuteMsg::Pull { bet_number } => {
+            let (denom, bet_size) = native_bet(&info)?;
+            execute_entropy_beacon_pull(deps, env, info.sender, denom, bet_size, bet_number)
+        }
+
+        // Bets and deposits in whitelisted cw20 tokens
+        ExecuteMsg::Receive(msg) => execute_receive(deps, env, info, msg),
 
         // #STEP 2:
-        // Handle receiving entropy from the beacon.
+        // Handle receiving entropy from the beacon,
+        // spinning the wheel and paying out the player.
         ExecuteMsg::ReceiveEntropy(data) => execute_recieve_entropy(deps, env, info, data),
 
-        // #STEP 3:
-        // Handle player placing a bet and spinning the wheel
-        ExecuteMsg::Spin { bet_number } => execute_spin(deps, env, info, bet_number),
+        // Return the bet of a game the beacon never answered in time
+        ExecuteMsg::Refund { game_id } => execute_refund(deps, env, info, game_id),
+
+        // Fee recipients collect what was accrued to them
+        ExecuteMsg::ClaimFees {} => execute_claim_fees(deps, info),
+
+        // Owner only: change the config
+        ExecuteMsg::UpdateConfig(update) => execute_update_config(deps, info, update),
+
+        // Owner only: manage the denoms bets can be placed in
+        ExecuteMsg::AddDenom(denom_config) => execute_add_denom(deps, info, denom_config),
+        ExecuteMsg::RemoveDenom { denom } => execute_remove_denom(deps, info, denom),
+
+        // Two-step ownership transfer and renouncing ownership
+        ExecuteMsg::ProposeOwner { new_owner, expiry } => {
+            execute_propose_owner(deps, env, info, new_owner, expiry)
+        }
+        ExecuteMsg::AcceptOwnership {} => execute_accept_ownership(deps, env, info),
+        ExecuteMsg::CancelOwnershipTransfer {} => execute_cancel_ownership_transfer(deps, info),
+        ExecuteMsg::RenounceOwnership {} => execute_renounce_ownership(deps, info),
+
+        // Circuit breaker for new games
+        ExecuteMsg::SetGuardian { guardian_addr } => execute_set_guardian(deps, info, guardian_addr),
+        ExecuteMsg::Pause {} => execute_pause(deps, info),
+        ExecuteMsg::Unpause {} => execute_unpause(deps, info),
+
+        // Owner funding of the house
+        ExecuteMsg::DepositBankroll {} => {
+            let (denom, amount) = native_payment(&info)?;
+            execute_deposit_bankroll(deps, info.sender, denom, amount)
+        }
+
+        // Liquidity providers backing the house
+        ExecuteMsg::DepositLiquidity {} => {
+            let (denom, amount) = native_payment(&info)?;
+            execute_deposit_liquidity(deps, info.sender, denom, amount)
+        }
+        ExecuteMsg::UnbondLiquidity { denom, shares } => {
+            execute_unbond_liquidity(deps, env, info, denom, shares)
+        }
+        ExecuteMsg::WithdrawLiquidity {} => execute_withdraw_liquidity(deps, env, info),
     }
 }
 
 #[cfg_attr(not(feature = "library"), entry_point)]
 pub fn query(deps: Deps, _env: Env, msg: QueryMsg) -> StdResult<Binary> {
     match msg {
+        QueryMsg::Config {} => {
+            let config = CONFIG.load(deps.storage)?;
+
+            to_binary(&ConfigResponse {
+                entropy_beacon_addr: config.entropy_beacon_addr,
+                owner: config.owner_addr,
+                guardian: config.guardian_addr,
+                wheel: config.wheel,
+                min_house_edge: config.min_house_edge,
+                max_exposure: config.max_exposure,
+                beacon_fee_denom: config.beacon_fee_denom,
+                fee_recipients: config.fee_recipients,
+                rate_limit: config.rate_limit,
+                entropy_timeout: config.entropy_timeout,
+                callback_gas_limit: config.callback_gas_limit,
+                unbonding_period: config.unbonding_period,
+            })
+        }
         QueryMsg::Game { idx } => {
-            let game = GAME.load(deps.storage, idx.u128())?;
+            let game = games().load(deps.storage, idx.u128())?;
 
-            to_binary(&GameResponse {
-                idx,
-                player: game.player.clone(),
-                result: game.result.as_ref().map(|x| x.clone()),
-                win: game.win(game.bet_size),
+            to_binary(&GameResponse::from(game))
+        }
+        QueryMsg::GamesByPlayer {
+            player,
+            start_after,
+            limit,
+            order,
+        } => to_binary(&query_games_by_player(deps, player, start_after, limit, order)?),
+        QueryMsg::Games { start_after, limit } => {
+            to_binary(&query_games(deps, start_after, limit)?)
+        }
+        QueryMsg::Stats {} => to_binary(&query_stats(deps)?),
+        QueryMsg::Denom { denom } => to_binary(&DENOMS.load(deps.storage, denom.key())?),
+        QueryMsg::Denoms {} => to_binary(&query_denoms(deps)?),
+        QueryMsg::Odds { denom } => to_binary(&query_odds(deps, denom)?),
+        QueryMsg::Bankroll { denom } => to_binary(&load_bankroll(deps.storage, &denom)?),
+        QueryMsg::Vault { denom } => to_binary(&query_vault(deps, denom)?),
+        QueryMsg::Position { address, denom } => {
+            to_binary(&query_position(deps, address, denom)?)
+        }
+        QueryMsg::MaxBet { denom } => to_binary(&query_max_bet(deps, denom)?),
+        QueryMsg::AccruedFees { address } => to_binary(&query_accrued_fees(deps, address)?),
+        QueryMsg::PlayerStats { address, denom } => {
+            to_binary(&query_player_stats(deps, address, denom)?)
+        }
+        QueryMsg::PauseStatus {} => {
+            let config = CONFIG.load(deps.storage)?;
+
+            to_binary(&PauseStatusResponse {
+                paused: PAUSED.load(deps.storage)?,
+                guardian: config.guardian_addr,
+            })
+        }
+        QueryMsg::Ownership {} => {
+            let config = CONFIG.load(deps.storage)?;
+            let pending = PENDING_OWNER.may_load(deps.storage)?;
+
+            to_binary(&OwnershipResponse {
+                owner: config.owner_addr,
+                pending_owner: pending.as_ref().map(|pending| pending.new_owner.clone()),
+                pending_expiry: pending.map(|pending| pending.expires),
             })
         }
     }
 }
 
+// Pagination limits for game listings
+const DEFAULT_LIMIT: u32 = 10;
+const MAX_LIMIT: u32 = 30;
+
+pub fn query_games_by_player(
+    deps: Deps,
+    player: String,
+    start_after: Option<Uint128>,
+    limit: Option<u32>,
+    order: Option<OrderBy>,
+) -> StdResult<GamesResponse> {
+    let player = deps.api.addr_validate(&player)?;
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let order: Order = order.unwrap_or(OrderBy::Descending).into();
+
+    // start_after is exclusive and bounds whichever end the listing starts from
+    let start_after = start_after.map(|idx| Bound::exclusive(idx.u128()));
+    let (min, max) = match order {
+        Order::Ascending => (start_after, None),
+        Order::Descending => (None, start_after),
+    };
+
+    let games = games()
+        .idx
+        .player
+        .prefix(player)
+        .range(deps.storage, min, max, order)
+        .take(limit)
+        .map(|item| item.map(|(_, game)| GameResponse::from(game)))
+        .collect::<StdResult<Vec<_>>>()?;
+
+    Ok(GamesResponse { games })
+}
+
+pub fn query_games(
+    deps: Deps,
+    start_after: Option<Uint128>,
+    limit: Option<u32>,
+) -> StdResult<GamesResponse> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let start = start_after.map(|idx| Bound::exclusive(idx.u128()));
+
+    let games = games()
+        .range(deps.storage, start, None, Order::Ascending)
+        .take(limit)
+        .map(|item| item.map(|(_, game)| GameResponse::from(game)))
+        .collect::<StdResult<Vec<_>>>()?;
+
+    Ok(GamesResponse { games })
+}
+
+pub fn query_stats(deps: Deps) -> StdResult<StatsResponse> {
+    let config = CONFIG.load(deps.storage)?;
+
+    let stats = STATS
+        .range(deps.storage, None, None, Order::Ascending)
+        .map(|item| {
+            let (denom, stats) = item?;
+
+            // A bet on a segment returns its payout with probability weight / total weight
+            let theoretical_rtp = match DENOMS.may_load(deps.storage, &denom)? {
+                Some(denom_config) => denom_config.wheel(&config.wheel).segment_rtp()?,
+                None => vec![],
+            };
+            let realised_rtp = if stats.total_wagered.is_zero() {
+                Decimal::zero()
+            } else {
+                Decimal::from_ratio(stats.total_paid_out, stats.total_wagered)
+            };
+            Ok(DenomStats {
+                denom,
+                stats,
+                realised_rtp,
+                theoretical_rtp,
+            })
+        })
+        .collect::<StdResult<Vec<_>>>()?;
+
+    Ok(StatsResponse { stats })
+}
+
+pub fn query_denoms(deps: Deps) -> StdResult<DenomsResponse> {
+    let denoms = DENOMS
+        .range(deps.storage, None, None, Order::Ascending)
+        .map(|item| item.map(|(_, denom_config)| denom_config))
+        .collect::<StdResult<Vec<_>>>()?;
+
+    Ok(DenomsResponse { denoms })
+}
+
+pub fn query_vault(deps: Deps, denom: AssetInfo) -> StdResult<VaultResponse> {
+    let config = CONFIG.load(deps.storage)?;
+    let bankroll = BANKROLL
+        .may_load(deps.storage, denom.key())?
+        .unwrap_or_default();
+    let reserved = load_reserved(deps.storage, &denom)?;
+
+    Ok(VaultResponse {
+        total_shares: bankroll.total_shares,
+        share_price: bankroll.share_price(reserved),
+        entropy_fees: bankroll.entropy_fees,
+        pays_entropy_fees: denom == AssetInfo::Native(config.beacon_fee_denom),
+        denom,
+        unbonding_period: config.unbonding_period,
+    })
+}
+
+pub fn query_position(deps: Deps, address: String, denom: AssetInfo) -> StdResult<PositionResponse> {
+    let address = deps.api.addr_validate(&address)?;
+    let bankroll = BANKROLL
+        .may_load(deps.storage, denom.key())?
+        .unwrap_or_default();
+    let reserved = load_reserved(deps.storage, &denom)?;
+    let shares = SHARES
+        .may_load(deps.storage, (denom.key(), &address))?
+        .unwrap_or_default();
+
+    Ok(PositionResponse {
+        shares,
+        value: bankroll.value_of(shares, reserved),
+        unbonding: UNBONDING
+            .may_load(deps.storage, &address)?
+            .unwrap_or_default()
+            .into_iter()
+            .filter(|unbonding| unbonding.denom == denom)
+            .collect(),
+        address,
+    })
+}
+
+pub fn query_max_bet(deps: Deps, denom: AssetInfo) -> StdResult<MaxBetResponse> {
+    let config = CONFIG.load(deps.storage)?;
+    let denom_config = DENOMS.load(deps.storage, denom.key())?;
+    let bankroll = load_bankroll(deps.storage, &denom)?;
+
+    Ok(MaxBetResponse {
+        max_bet: max_bet(&config, &denom_config, bankroll.free),
+        denom,
+    })
+}
+
+pub fn query_odds(deps: Deps, denom: AssetInfo) -> StdResult<OddsResponse> {
+    let config = CONFIG.load(deps.storage)?;
+    let denom_config = DENOMS.load(deps.storage, denom.key())?;
+    let wheel = denom_config.wheel(&config.wheel);
+
+    let segments = wheel
+        .segments
+        .iter()
+        .zip(wheel.segment_rtp()?)
+        .map(|(segment, rtp)| SegmentOdds {
+            label: segment.label.clone(),
+            weight: segment.weight,
+            probability: wheel.probability(segment),
+            payout: segment.payout,
+            rtp,
+        })
+        .collect();
+    let rtp = wheel.rtp()?;
+
+    Ok(OddsResponse {
+        segments,
+        rtp,
+        house_edge: Decimal::one().checked_sub(rtp).unwrap_or_default(),
+    })
+}
+
+pub fn query_accrued_fees(deps: Deps, address: String) -> StdResult<AccruedFeesResponse> {
+    let address = deps.api.addr_validate(&address)?;
+    let fees = FEES.may_load(deps.storage, &address)?.unwrap_or_default();
+
+    Ok(AccruedFeesResponse { address, fees })
+}
+
+pub fn query_player_stats(
+    deps: Deps,
+    address: String,
+    denom: AssetInfo,
+) -> StdResult<PlayerStatsResponse> {
+    let address = deps.api.addr_validate(&address)?;
+    let stats = PLAYER_STATS
+        .may_load(deps.storage, (denom.key(), &address))?
+        .unwrap_or_default();
+
+    Ok(PlayerStatsResponse {
+        address,
+        denom,
+        games_played: stats.games_played,
+        total_wagered: stats.total_wagered,
+        total_won: stats.total_won,
+        net_gain: stats.total_won.saturating_sub(stats.total_wagered),
+        net_loss: stats.total_wagered.saturating_sub(stats.total_won),
+        biggest_win: stats.biggest_win,
+        current_win_streak: stats.current_win_streak,
+        current_lose_streak: stats.current_lose_streak,
+        best_win_streak: stats.best_win_streak,
+        best_lose_streak: stats.best_lose_streak,
+    })
+}
+
+// Only a contract already on this version's storage layout can move to this code,
+// anything older has to be redeployed
 #[entry_point]
-pub fn migrate(deps: DepsMut, _env: Env, msg: MigrateMsg) -> StdResult<Response> {
+pub fn migrate(deps: DepsMut, _env: Env, _msg: MigrateMsg) -> Result<Response, ContractError> {
+    let stored = get_contract_version(deps.storage)?;
+    if stored.contract != CONTRACT_NAME || stored.version != CONTRACT_VERSION {
+        return Err(ContractError::CannotMigrate {
+            contract: stored.contract,
+            version: stored.version,
+        });
+    }
+
+    Ok(Response::new()
+        .add_attribute("method", "migrate")
+        .add_attribute("version", CONTRACT_VERSION))
+}
+
+// Tokens sent by a cw20 contract, on behalf of whoever sent them
+pub fn execute_receive(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    wrapper: Cw20ReceiveMsg,
+) -> Result<Response, ContractError> {
+    // Only the token contract itself can vouch for the amount, so the
+    // sender of this message decides the denom
+    let denom = AssetInfo::Cw20(info.sender);
+    let sender = deps.api.addr_validate(&wrapper.sender)?;
+
+    match from_binary(&wrapper.msg)? {
+        ReceiveMsg::Pull { bet_number } => {
+            execute_entropy_beacon_pull(deps, env, sender, denom, wrapper.amount, bet_number)
+        }
+        ReceiveMsg::DepositBankroll {} => {
+            execute_deposit_bankroll(deps, sender, denom, wrapper.amount)
+        }
+        ReceiveMsg::DepositLiquidity {} => {
+            execute_deposit_liquidity(deps, sender, denom, wrapper.amount)
+        }
+    }
+}
+
+// The single coin attached to a message
+fn native_payment(info: &MessageInfo) -> Result<(AssetInfo, Uint128), ContractError> {
+    let coin = one_coin(info)?;
+    Ok((AssetInfo::Native(Denom::from(coin.denom.as_str())), coin.amount))
+}
+
+// The single coin attached to a pull, which is the bet
+fn native_bet(info: &MessageInfo) -> Result<(AssetInfo, Uint128), ContractError> {
+    match info.funds.as_slice() {
+        [] => Err(ContractError::InvalidBetAmount {}),
+        [coin] => Ok((AssetInfo::Native(Denom::from(coin.denom.as_str())), coin.amount)),
+        _ => Err(ContractError::InvalidCoin {}),
+    }
+}
+
+// Whether a denom can be bet and deposited in
+fn is_whitelisted(storage: &dyn Storage, denom: &AssetInfo) -> StdResult<bool> {
+    Ok(DENOMS
+        .may_load(storage, denom.key())?
+        .is_some_and(|denom_config| &denom_config.denom == denom))
+}
+
+pub fn execute_deposit_bankroll(
+    deps: DepsMut,
+    sender: Addr,
+    denom: AssetInfo,
+    amount: Uint128,
+) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    assert_owner(&config, &sender)?;
+
+    if !is_whitelisted(deps.storage, &denom)? {
+        return Err(ContractError::InvalidToken {});
+    }
+    let shares = deposit_liquidity(deps.storage, &denom, &sender, amount)?;
+
+    Ok(Response::new()
+        .add_attribute("method", "deposit_bankroll")
+        .add_attribute("denom", denom.to_string())
+        .add_attribute("amount", amount)
+        .add_attribute("shares", shares))
+}
+
+pub fn execute_deposit_liquidity(
+    deps: DepsMut,
+    provider: Addr,
+    denom: AssetInfo,
+    amount: Uint128,
+) -> Result<Response, ContractError> {
+    if !is_whitelisted(deps.storage, &denom)? {
+        return Err(ContractError::InvalidToken {});
+    }
+    let shares = deposit_liquidity(deps.storage, &denom, &provider, amount)?;
+
+    Ok(Response::new()
+        .add_attribute("method", "deposit_liquidity")
+        .add_attribute("provider", provider)
+        .add_attribute("denom", denom.to_string())
+        .add_attribute("amount", amount)
+        .add_attribute("shares", shares))
+}
+
+pub fn execute_unbond_liquidity(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    denom: AssetInfo,
+    shares: Uint128,
+) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+
+    let key = (denom.key(), &info.sender);
+    let available = SHARES.may_load(deps.storage, key)?.unwrap_or_default();
+    if shares.is_zero() || shares > available {
+        return Err(ContractError::InsufficientShares { available });
+    }
+    SHARES.save(deps.storage, key, &(available - shares))?;
+
+    // The shares keep sharing in the game result until they are withdrawn
+    let release_at = config.unbonding_period.after(&env.block);
+    let mut unbonding = UNBONDING
+        .may_load(deps.storage, &info.sender)?
+        .unwrap_or_default();
+    unbonding.push(Unbonding {
+        denom: denom.clone(),
+        shares,
+        release_at,
+    });
+    UNBONDING.save(deps.storage, &info.sender, &unbonding)?;
+
+    Ok(Response::new()
+        .add_attribute("method", "unbond_liquidity")
+        .add_attribute("provider", info.sender)
+        .add_attribute("denom", denom.to_string())
+        .add_attribute("shares", shares)
+        .add_attribute("release_at", release_at.to_string()))
+}
+
+pub fn execute_withdraw_liquidity(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+) -> Result<Response, ContractError> {
+    let (released, pending): (Vec<_>, Vec<_>) = UNBONDING
+        .may_load(deps.storage, &info.sender)?
+        .unwrap_or_default()
+        .into_iter()
+        .partition(|unbonding| unbonding.release_at.is_expired(&env.block));
+    if released.is_empty() {
+        return Err(ContractError::NothingToWithdraw {});
+    }
+
+    let mut response = Response::new()
+        .add_attribute("method", "withdraw_liquidity")
+        .add_attribute("provider", info.sender.clone());
+    for unbonding in released {
+        // Redeem at the current share price, which leaves out what pending games may need
+        let reserved = load_reserved(deps.storage, &unbonding.denom)?;
+        let mut bankroll = BANKROLL.load(deps.storage, unbonding.denom.key())?;
+        let amount = bankroll.redeem(unbonding.shares, reserved)?;
+        BANKROLL.save(deps.storage, unbonding.denom.key(), &bankroll)?;
+
+        // Shares of a wiped out bankroll are burnt without anything to send
+        response = response
+            .add_attribute("shares", unbonding.shares)
+            .add_attribute("amount", amount);
+        if !amount.is_zero() {
+            response = response.add_message(unbonding.denom.transfer_msg(&info.sender, amount)?);
+        }
+    }
+    UNBONDING.save(deps.storage, &info.sender, &pending)?;
+
+    Ok(response)
+}
+
+// Add funds to the bankroll of a denom and credit the shares they buy
+fn deposit_liquidity(
+    storage: &mut dyn Storage,
+    denom: &AssetInfo,
+    provider: &Addr,
+    amount: Uint128,
+) -> Result<Uint128, ContractError> {
+    let reserved = load_reserved(storage, denom)?;
+    let mut bankroll = BANKROLL.may_load(storage, denom.key())?.unwrap_or_default();
+    let shares = bankroll.deposit(amount, reserved)?;
+    if shares.is_zero() {
+        return Err(ContractError::DepositTooSmall {});
+    }
+    BANKROLL.save(storage, denom.key(), &bankroll)?;
+    SHARES.update(storage, (denom.key(), provider), |held| -> StdResult<_> {
+        Ok(held.unwrap_or_default().checked_add(shares)?)
+    })?;
+    Ok(shares)
+}
+
+// Only the current owner may pass, nobody does once ownership is renounced
+pub fn assert_owner(config: &Config, sender: &Addr) -> Result<(), ContractError> {
+    if config.owner_addr.as_ref() != Some(sender) {
+        return Err(ContractError::Unauthorized {});
+    }
+    Ok(())
+}
+
+pub fn execute_propose_owner(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    new_owner: String,
+    expiry: Option<Expiration>,
+) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    assert_owner(&config, &info.sender)?;
+
+    // An offer that is already expired could never be accepted
+    let expires = expiry.unwrap_or_default();
+    if expires.is_expired(&env.block) {
+        return Err(ContractError::OwnershipTransferExpired {});
+    }
+
+    // A new proposal replaces any previous one
+    let pending = PendingOwner {
+        new_owner: deps.api.addr_validate(&new_owner)?,
+        expires,
+    };
+    PENDING_OWNER.save(deps.storage, &pending)?;
+
+    Ok(Response::new()
+        .add_attribute("method", "propose_owner")
+        .add_attribute("pending_owner", pending.new_owner)
+        .add_attribute("expires", pending.expires.to_string()))
+}
+
+pub fn execute_accept_ownership(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+) -> Result<Response, ContractError> {
+    let pending = PENDING_OWNER
+        .may_load(deps.storage)?
+        .ok_or(ContractError::NoPendingOwner {})?;
+
+    // Only the proposed owner can accept, and only in time
+    if info.sender != pending.new_owner {
+        return Err(ContractError::Unauthorized {});
+    }
+    if pending.expires.is_expired(&env.block) {
+        return Err(ContractError::OwnershipTransferExpired {});
+    }
+
+    let mut config = CONFIG.load(deps.storage)?;
+    config.owner_addr = Some(pending.new_owner.clone());
+    CONFIG.save(deps.storage, &config)?;
+    PENDING_OWNER.remove(deps.storage);
+
+    Ok(Response::new()
+        .add_attribute("method", "accept_ownership")
+        .add_attribute("owner", pending.new_owner))
+}
+
+pub fn execute_cancel_ownership_transfer(
+    deps: DepsMut,
+    info: MessageInfo,
+) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    assert_owner(&config, &info.sender)?;
+
+    if PENDING_OWNER.may_load(deps.storage)?.is_none() {
+        return Err(ContractError::NoPendingOwner {});
+    }
+    PENDING_OWNER.remove(deps.storage);
+
+    Ok(Response::new().add_attribute("method", "cancel_ownership_transfer"))
+}
+
+pub fn execute_renounce_ownership(
+    deps: DepsMut,
+    info: MessageInfo,
+) -> Result<Response, ContractError> {
     let mut config = CONFIG.load(deps.storage)?;
-    config.fee_amount = msg.fee_amount;
+    assert_owner(&config, &info.sender)?;
+
+    // Without an owner, no pending transfer may complete either
+    config.owner_addr = None;
     CONFIG.save(deps.storage, &config)?;
+    PENDING_OWNER.remove(deps.storage);
 
-    Ok(Response::new())
+    Ok(Response::new()
+        .add_attribute("method", "renounce_ownership")
+        .add_attribute("previous_owner", info.sender))
 }
 
-// Validate the players bet amount and number
-pub fn execute_validate_bet(
-    deps: &DepsMut,
-    env: &Env,
+pub fn execute_set_guardian(
+    deps: DepsMut,
     info: MessageInfo,
-    player_bet_amount: Uint128,
-    player_bet_number: Uint128,
-) -> bool {
-
-    let mut config = CONFIG.load(deps.storage).unwrap();
-
-    // Get the balance of the house bankroll (contract address balance)
-    let bankroll_balance = match 
-    deps.querier
-    .query_balance(
-        env.contract.address.to_string()
-        , "ukuji".to_string()) {
-            Ok(balance) => balance,
-            Err(_) => return false,
-        };
+    guardian_addr: Option<String>,
+) -> Result<Response, ContractError> {
+    let mut config = CONFIG.load(deps.storage)?;
+    assert_owner(&config, &info.sender)?;
+
+    config.guardian_addr = guardian_addr
+        .map(|guardian| deps.api.addr_validate(&guardian))
+        .transpose()?;
+    CONFIG.save(deps.storage, &config)?;
+
+    Ok(Response::new()
+        .add_attribute("method", "set_guardian")
+        .add_attribute(
+            "guardian",
+            config
+                .guardian_addr
+                .map(|guardian| guardian.to_string())
+                .unwrap_or_else(|| "none".to_string()),
+        ))
+}
 
-    config.house_bankroll = bankroll_balance.clone(); 
+pub fn execute_pause(deps: DepsMut, info: MessageInfo) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
 
-    // Check that the players bet number is between 0 and 6
-    if player_bet_number > Uint128::new(6) {
-        return false;
+    // Both the owner and the guardian can pull the emergency brake
+    if config.guardian_addr.as_ref() != Some(&info.sender) {
+        assert_owner(&config, &info.sender)?;
     }
+    PAUSED.save(deps.storage, &true)?;
 
-    // Check that only one denom was sent
-    let coin = match one_coin(&info) {
-        Ok(coin) => coin,
-        Err(_) => return false,
-    };
+    Ok(Response::new()
+        .add_attribute("method", "pause")
+        .add_attribute("sender", info.sender))
+}
 
-    // Check that the denom is the same as the token in the bankroll ("ukuji")
-    if coin.denom != bankroll_balance.denom {
-        return false;
+pub fn execute_unpause(deps: DepsMut, info: MessageInfo) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+
+    // Only the owner can resume the game
+    assert_owner(&config, &info.sender)?;
+    PAUSED.save(deps.storage, &false)?;
+
+    Ok(Response::new()
+        .add_attribute("method", "unpause")
+        .add_attribute("sender", info.sender))
+}
+
+pub fn execute_claim_fees(deps: DepsMut, info: MessageInfo) -> Result<Response, ContractError> {
+    let fees = FEES
+        .may_load(deps.storage, &info.sender)?
+        .unwrap_or_default();
+    if fees.iter().all(|fee| fee.amount.is_zero()) {
+        return Err(ContractError::NothingToClaim {});
+    }
+    FEES.remove(deps.storage, &info.sender);
+
+    let mut response = Response::new()
+        .add_attribute("method", "claim_fees")
+        .add_attribute("recipient", info.sender.clone());
+    for fee in fees.into_iter().filter(|fee| !fee.amount.is_zero()) {
+        response = response
+            .add_attribute("denom", fee.denom.to_string())
+            .add_attribute("amount", fee.amount)
+            .add_message(fee.denom.transfer_msg(&info.sender, fee.amount)?);
     }
 
-    // Get the balance of the house bankroll (contract address balance)
-    // let bankroll_balance = deps.querier
-    // .query_balance(
-    //     env.contract.address.to_string()
-    //     , "ukuji".to_string()
-    //     )?;
+    Ok(response)
+}
+
+pub fn execute_add_denom(
+    deps: DepsMut,
+    info: MessageInfo,
+    denom_config: DenomConfig,
+) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    assert_owner(&config, &info.sender)?;
+
+    // New terms only apply to new bets, pending games keep the wheel they were placed on
+    validate_denom_config(deps.api, &config, &denom_config)?;
+    DENOMS.save(deps.storage, denom_config.denom.key(), &denom_config)?;
+
+    Ok(Response::new()
+        .add_attribute("method", "add_denom")
+        .add_attribute("denom", denom_config.denom.to_string())
+        .add_attribute("min_bet", denom_config.min_bet)
+        .add_attribute("max_bet", denom_config.max_bet)
+        .add_attribute("fee", denom_config.fee.to_string()))
+}
 
+pub fn execute_remove_denom(
+    deps: DepsMut,
+    info: MessageInfo,
+    denom: AssetInfo,
+) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    assert_owner(&config, &info.sender)?;
 
-    // Make sure the player's bet_amount does not exceed 10% of house bankroll
-    if player_bet_amount
-        > bankroll_balance.amount
-        .checked_div(Uint128::new(10))
-        .unwrap() {
-        return false;
+    if !DENOMS.has(deps.storage, denom.key()) {
+        return Err(ContractError::UnknownDenom {
+            denom: denom.to_string(),
+        });
     }
+    DENOMS.remove(deps.storage, denom.key());
 
-    true
+    Ok(Response::new()
+        .add_attribute("method", "remove_denom")
+        .add_attribute("denom", denom.to_string()))
 }
 
-pub fn execute_spin(
+pub fn execute_update_config(
     deps: DepsMut,
-    _env: Env,
     info: MessageInfo,
-    bet_number: Uint128,
+    update: UpdateConfigMsg,
 ) -> Result<Response, ContractError> {
-    {
-        // Load the game state
-        let config = CONFIG.load(deps.storage).unwrap();
+    let mut config = CONFIG.load(deps.storage)?;
 
-        // Check that only one denom was sent
-        let coin = one_coin(&info).unwrap();
+    // Only the owner can change the config
+    assert_owner(&config, &info.sender)?;
 
-        // Check that the denom is the same as the token in the state
-        if coin.denom != config.house_bankroll.denom {
-            return Err(ContractError::InvalidToken {});
-        }
-        // Check that the amount is the same as the play_amount in the state
-        if coin.amount != info.funds[0].amount {
-            return Err(ContractError::InsufficientFunds {});
-        }
-        // Get the current game index
-        let idx = IDX.load(deps.storage)?;
-
-        // Check if there is a game at the current index
-        let game = GAME.may_load(deps.storage, idx.u128())?;
-
-        // Check if the game has been played
-        match game {
-            // The game has been played
-            Some(mut game) => {
-
-                // let game = GAME.load(deps.storage, idx.u128())?;
-
-                // only let players access their own game
-                if game.player != info.sender {
-                    return Err(ContractError::Unauthorized {});
-                }
-
-                // Get the result of the game
-                let outcome = game.result.clone().unwrap();
-
-                // Calculate the payout
-                let calculated_payout =
-                    calculate_payout(info.funds[0].amount, outcome[0], config.rule_set);
-
-                // Return the payout to the player if they win
-                if game.win(bet_number) {
-                    let payout_coin = Coin {
-                        denom: coin.denom,
-                        amount: calculated_payout,
-                    };
-                    // Player has won, update game state
-                    game.win = Some(true);
-
-                    // Send the payout to the player
-                    let payout_msg = BankMsg::Send {
-                        to_address: game.player.to_string(),
-                        amount: vec![payout_coin],
-                    };
-
-                    // generate the response
-                    let response = Response::new()
-                        .add_attribute("game", idx.u128().to_string())
-                        .add_attribute("player", game.player.clone())
-                        .add_attribute("result", "win")
-                        .add_attribute("payout", calculated_payout.to_string())
-                        // .add_message(send_msg) // Add the collection message to the response //TODO: Can i add two messages to the response?
-                        .add_message(payout_msg.clone()); // Add the payout message to the response
-
-                    // Player has won, update game state
-                    game.played = true;
-                    game.bet_number = bet_number;
-                    game.payout = calculated_payout;
-                    game.result = Some(outcome);
-                    GAME.save(deps.storage, idx.u128(), &game)?;
-
-                    // Increment gameID for the next game
-                    IDX.save(deps.storage, &(idx + Uint128::from(1u128)))?;
-
-                    Ok(response.add_message(payout_msg))
-                } else {
-                    // Player has lost, update game state
-                    game.played = true;
-                    game.win = Some(false);
-                    game.bet_number = bet_number;
-                    game.payout = Uint128::zero();
-                    game.result = Some(outcome);
-                    GAME.save(deps.storage, idx.u128(), &game)?;
-
-                    // Increment gameID for the next game
-                    IDX.save(deps.storage, &(idx + Uint128::from(1u128)))?;
-
-                    return Ok(Response::new()
-                        .add_attribute("game", idx.u128().to_string())
-                        .add_attribute("player", game.player.to_string())
-                        .add_attribute("result", "lose"));
-                }
-            }
-            // Game has not been played
-            None => {
-                Ok(Response::new()
-                    .add_attribute("game", "not played")
-                    .add_attribute("result", "pending"))
-            }
-        }
+    // Apply every field that was given and describe it in the response
+    let mut response = Response::new().add_attribute("method", "update_config");
+
+    if let Some(entropy_beacon_addr) = update.entropy_beacon_addr {
+        config.entropy_beacon_addr = deps.api.addr_validate(&entropy_beacon_addr)?;
+        response = response.add_attribute("entropy_beacon_addr", entropy_beacon_addr);
+    }
+    if let Some(wheel) = update.wheel {
+        config.wheel = wheel;
+        response = response.add_attribute("wheel", "updated");
+    }
+    if let Some(min_house_edge) = update.min_house_edge {
+        config.min_house_edge = min_house_edge;
+        response = response.add_attribute("min_house_edge", min_house_edge.to_string());
+    }
+    if let Some(max_exposure) = update.max_exposure {
+        config.max_exposure = max_exposure;
+        response = response.add_attribute("max_exposure", max_exposure.to_string());
+    }
+    if let Some(beacon_fee_denom) = update.beacon_fee_denom {
+        response = response.add_attribute("beacon_fee_denom", beacon_fee_denom.to_string());
+        config.beacon_fee_denom = beacon_fee_denom;
     }
+    if let Some(fee_recipients) = update.fee_recipients {
+        config.fee_recipients = validate_fee_recipients(deps.api, fee_recipients)?;
+        response = response.add_attribute("fee_recipients", "updated");
+    }
+    if let Some(rate_limit) = update.rate_limit {
+        config.rate_limit = rate_limit;
+        response = response.add_attribute("rate_limit", "updated");
+    }
+    if let Some(entropy_timeout) = update.entropy_timeout {
+        config.entropy_timeout = entropy_timeout;
+        response = response.add_attribute("entropy_timeout", entropy_timeout.to_string());
+    }
+    if let Some(unbonding_period) = update.unbonding_period {
+        config.unbonding_period = unbonding_period;
+        response = response.add_attribute("unbonding_period", unbonding_period.to_string());
+    }
+    if let Some(callback_gas_limit) = update.callback_gas_limit {
+        config.callback_gas_limit = callback_gas_limit;
+        response = response.add_attribute("callback_gas_limit", callback_gas_limit.to_string());
+    }
+
+    // The updated config has to be as consistent as a freshly instantiated one,
+    // and a higher house edge has to hold for the wheels of the denoms too
+    validate_config(&config)?;
+    for item in DENOMS.range(deps.storage, None, None, Order::Ascending) {
+        validate_denom_config(deps.api, &config, &item?.1)?;
+    }
+    CONFIG.save(deps.storage, &config)?;
+
+    Ok(response)
+}
+
+// A bet that passed validation, ready to be turned into a game
+pub struct ValidatedBet {
+    pub denom_config: DenomConfig,
+    // What is wagered, the stake minus the fee
+    pub bet_size: Uint128,
+    pub fee: Uint128,
+    pub bet_number: u8,
+}
+
+// Validate the players bet amount and number
+pub fn validate_bet(
+    deps: Deps,
+    config: &Config,
+    denom: &AssetInfo,
+    stake: Uint128,
+    bet_number: Uint128,
+) -> Result<ValidatedBet, ContractError> {
+    // Check that the bet is in a whitelisted denom
+    let denom_config = DENOMS
+        .may_load(deps.storage, denom.key())?
+        .filter(|denom_config| &denom_config.denom == denom)
+        .ok_or(ContractError::InvalidToken {})?;
+
+    // Check that the players bet number is on the wheel of the denom
+    let wheel = denom_config.wheel(&config.wheel);
+    let bet_number = match u8::try_from(bet_number.u128()) {
+        Ok(number) if (number as usize) < wheel.segments.len() => number,
+        _ => return Err(ContractError::InvalidBetNumber {}),
+    };
+
+    // Check that the stake is within the bet limits of the denom
+    if stake < denom_config.min_bet {
+        return Err(ContractError::BetTooSmall { min_bet: denom_config.min_bet });
+    }
+    if stake > denom_config.max_bet {
+        return Err(ContractError::BetTooLarge { max_bet: denom_config.max_bet });
+    }
+
+    // The fee comes out of the stake, which has to leave something to wager
+    let fee = denom_config.fee.stake_fee(stake);
+    if fee >= stake {
+        return Err(ContractError::StakeDoesNotCoverFee { stake, fee });
+    }
+
+    // Make sure the worst case payout stays within the exposure limit of the free
+    // bankroll, which doesn't count the bet until it is accepted
+    let bankroll = load_bankroll(deps.storage, &denom_config.denom)?;
+    let max_bet = max_bet(config, &denom_config, bankroll.free);
+    if stake > max_bet {
+        return Err(ContractError::BetTooLarge { max_bet });
+    }
+
+    Ok(ValidatedBet {
+        denom_config,
+        bet_size: stake - fee,
+        fee,
+        bet_number,
+    })
 }
 
 pub fn execute_recieve_entropy(
@@ -302,7 +1159,6 @@ pub fn execute_recieve_entropy(
 ) -> Result<Response, ContractError> {
     // Load the game state from the contract
     let config = CONFIG.load(deps.storage)?;
-    let idx = IDX.load(deps.storage)?;
 
     // Get the address of the entropy beacon
     let beacon_addr = config.entropy_beacon_addr;
@@ -324,124 +1180,465 @@ pub fn execute_recieve_entropy(
     let callback_data = data.msg;
     let callback_data: EntropyCallbackData = from_binary(&callback_data)?;
 
-    // Load game state with current game index
-    let mut game = GAME.load(deps.storage, idx.u128())?;
+    // Load the game this entropy was requested for
+    let idx = callback_data.game;
+    let mut game = games().load(deps.storage, idx.u128())?;
+
+    // The callback must belong to the player who requested it
+    if callback_data.original_sender != game.player {
+        return Err(ContractError::InvalidEntropyCallback {});
+    }
+
+    // Entropy arriving after the timeout is ignored, so the outcome can never
+    // decide whether a player would rather take the refund
+    if game.status == GameStatus::AwaitingEntropy && game.expires.is_expired(&env.block) {
+        game.transition(GameStatus::Expired)?;
+        games().save(deps.storage, idx.u128(), &game)?;
 
-    // gets a result (0-6) from the entropy, and sets game state to played
-    game.result = Some(get_outcome_from_entropy(&entropy));
-    game.played = true;
+        return Ok(Response::new()
+            .add_attribute("game", idx)
+            .add_attribute("player", game.player)
+            .add_attribute("result", "expired"));
+    }
+
+    // A game that timed out may already be expired or refunded, the callback still
+    // succeeds so the beacon doesn't see it fail
+    let timed_out = match game.status {
+        GameStatus::Expired => Some("expired"),
+        GameStatus::Refunded => Some("refunded"),
+        _ => None,
+    };
+    if let Some(result) = timed_out {
+        return Ok(Response::new()
+            .add_attribute("game", idx)
+            .add_attribute("player", game.player)
+            .add_attribute("result", result));
+    }
 
-    GAME.save(deps.storage, idx.u128(), &game)?;
+    // Land on a segment of the wheel the bet was placed on, weighted by the number
+    // of slots it takes up
+    let outcome = get_segment_from_entropy(&entropy, &game.wheel);
+    game.outcome = Some(outcome);
+    let label = game
+        .wheel
+        .segment(outcome)
+        .map(|segment| segment.label.clone())
+        .unwrap_or_default();
+
+    // Settle the bet that was fixed at pull time
+    let response = Response::new()
+        .add_attribute("game", idx)
+        .add_attribute("player", game.player.clone())
+        .add_attribute("outcome", outcome.to_string())
+        .add_attribute("segment", label);
+
+    if game.win() {
+        // Player has won, pay out according to the segment
+        // (entropy can only be applied to a game that is still waiting for it)
+        game.transition(GameStatus::PaidOut)?;
+        let calculated_payout = calculate_payout(game.bet_size, outcome, &game.wheel)?;
+
+        // The bankroll pays the whole payout, a fee on the winnings comes out of it
+        let winnings_fee = game.fee_model.winnings_fee(game.bet_size, calculated_payout);
+        game.fee += winnings_fee;
+        game.payout = calculated_payout - winnings_fee;
+        games().save(deps.storage, idx.u128(), &game)?;
+        release_liability(deps.storage, &game)?;
+        update_bankroll(deps.storage, &game.token, |bankroll| bankroll.pay(calculated_payout))?;
+        accrue_fees(deps.storage, &config.fee_recipients, &game.token, game.fee)?;
+        record_settlement(deps.storage, &game)?;
+
+        // Send the payout to the player
+        let payout_msg = game.token.transfer_msg(&game.player, game.payout)?;
+
+        Ok(response
+            .add_attribute("result", "win")
+            .add_attribute("payout", game.payout.to_string())
+            .add_attribute("fee", game.fee.to_string())
+            .add_message(payout_msg))
+    } else {
+        // Player has lost, the bet stays in the house bankroll
+        game.transition(GameStatus::Resolved)?;
+        game.payout = Uint128::zero();
+        games().save(deps.storage, idx.u128(), &game)?;
+        release_liability(deps.storage, &game)?;
+        accrue_fees(deps.storage, &config.fee_recipients, &game.token, game.fee)?;
+        record_settlement(deps.storage, &game)?;
+
+        Ok(response
+            .add_attribute("result", "lose")
+            .add_attribute("fee", game.fee.to_string()))
+    }
+}
 
-    return Ok(Response::new()
-        .add_attribute("game", callback_data.game)
+pub fn execute_refund(
+    deps: DepsMut,
+    env: Env,
+    _info: MessageInfo,
+    game_id: Uint128,
+) -> Result<Response, ContractError> {
+    let mut game = games().load(deps.storage, game_id.u128())?;
+
+    // A game still waiting for entropy can only be refunded after its timeout
+    if game.status == GameStatus::AwaitingEntropy && !game.expires.is_expired(&env.block) {
+        return Err(ContractError::GameNotExpired {
+            game_id,
+            expires: game.expires,
+        });
+    }
+
+    // Only unsettled games can be refunded, and only once
+    game.transition(GameStatus::Refunded)?;
+    games().save(deps.storage, game_id.u128(), &game)?;
+    release_liability(deps.storage, &game)?;
+    update_bankroll(deps.storage, &game.token, |bankroll| bankroll.pay(game.bet_size))?;
+    record_stats(deps.storage, &game.token, |stats| stats.record_refund(&game))?;
+
+    // Return the whole stake to the player, the fee is only charged on settled games
+    let refund = game.bet_size + game.fee;
+    let refund_msg = game.token.transfer_msg(&game.player, refund)?;
+
+    Ok(Response::new()
+        .add_attribute("method", "refund")
+        .add_attribute("game", game_id)
         .add_attribute("player", game.player)
-        .add_attribute("result", "pending")
-        .add_attribute("callbackdata.game", callback_data.game.to_string())); 
+        .add_attribute("refund", refund.to_string())
+        .add_message(refund_msg))
 }
 
 pub fn execute_entropy_beacon_pull(
     deps: DepsMut,
     env: Env,
-    info: MessageInfo,
+    player: Addr,
+    denom: AssetInfo,
+    amount: Uint128,
     player_bet_number: Uint128,
 ) -> Result<Response, ContractError> {
     // Load the game config 
     let config = CONFIG.load(deps.storage)?;
 
-    // Check that players bet amount is <= 10% of house bankroll, bet num [0, 6], denom etc
-    if !execute_validate_bet(
-        &deps, 
-        &env, 
-        info.clone(), 
-        info.funds[0].amount, 
-        player_bet_number) {
-            return Err(ContractError::InvalidBet {});
-        }
+    // No new games while the contract is paused
+    if PAUSED.load(deps.storage)? {
+        return Err(ContractError::Paused {});
+    }
 
-    // Get the current gameID
+    // Check the bet is placed on the wheel, in a whitelisted denom and within its limits
+    let ValidatedBet { denom_config, bet_size, fee, bet_number } =
+        validate_bet(deps.as_ref(), &config, &denom, amount, player_bet_number)?;
+    let token = denom_config.denom.clone();
+    let wheel = denom_config.wheel(&config.wheel).clone();
+
+    // Check that the player isn't pulling too often or wagering too much
+    apply_rate_limit(
+        deps.storage,
+        &config.rate_limit,
+        &denom_config,
+        &player,
+        bet_size,
+        env.block.height,
+    )?;
+
+    // Allocate a fresh gameID so concurrent pulls never share a game
     let idx = IDX.load(deps.storage)?;
+    IDX.save(deps.storage, &(idx + Uint128::one()))?;
+
+    // Reserve the most the game may have to pay, so later bets can't promise the same
+    // funds: the payout the bet would win, or the wager a refund returns when the
+    // segment pays less than the stake
+    let liability = calculate_payout(bet_size, bet_number, &wheel)?.max(bet_size);
+    reserve_liability(deps.storage, &token, liability)?;
 
     // Create a new game state for this game 
     let game = Game {
-        player: info.sender.clone(),
-        bet_number: player_bet_number,
-        bet_size: info.funds[0].amount,
+        player: player.clone(),
+        bet_number: Uint128::from(bet_number),
+        bet_size,
+        token: token.clone(),
+        wheel,
         payout: Uint128::zero(), // Payout not yet decided in this step
-        result: None,
-        played: false,
-        win: None,
+        fee,
+        fee_model: denom_config.fee.clone(),
+        liability,
+        outcome: None,
+        status: GameStatus::AwaitingEntropy,
         game_id: idx,
+        expires: config.entropy_timeout.after(&env.block),
     };
 
     // Save the game state to the contract
-    GAME.save(deps.storage, idx.u128(), &game)?;
+    games().save(deps.storage, idx.u128(), &game)?;
 
-    let callback_gas_limit = 100_000u64;
+    let callback_gas_limit = config.callback_gas_limit;
 
     let beacon_fee = CalculateFeeQuery::query(deps.as_ref(), callback_gas_limit, config.entropy_beacon_addr.clone())?;
+    let beacon_fee = Uint128::from(beacon_fee);
+
+    // The wager joins the bankroll of its denom while the fee is held with the game
+    // until it settles
+    update_bankroll(deps.storage, &token, |bankroll| bankroll.receive(bet_size))?;
+
+    // The entropy is paid for out of the bankroll of the beacon's fee denom, which
+    // has to be whitelisted and keep what pending games may need
+    let mut funds = vec![];
+    if !beacon_fee.is_zero() {
+        let beacon_fee_denom = AssetInfo::Native(config.beacon_fee_denom.clone());
+        let free = if is_whitelisted(deps.storage, &beacon_fee_denom)? {
+            load_bankroll(deps.storage, &beacon_fee_denom)?.free
+        } else {
+            Uint128::zero()
+        };
+        if beacon_fee > free {
+            return Err(ContractError::BeaconFeeNotCovered {
+                denom: beacon_fee_denom.to_string(),
+                fee: beacon_fee,
+                free,
+            });
+        }
+        update_bankroll(deps.storage, &beacon_fee_denom, |bankroll| bankroll.pay_entropy_fee(beacon_fee))?;
+        funds.push(Coin {
+            denom: config.beacon_fee_denom.to_string(),
+            amount: beacon_fee,
+        });
+    }
 
     // Create a request for entropy from the Beacon contract
-    let mut msgs = vec![EntropyRequest {
+    let msgs = vec![EntropyRequest {
         callback_gas_limit, 
         callback_address: env.contract.address.clone(),
-        funds: vec![Coin {
-            denom: config.token.to_string(),
-            amount: Uint128::from(beacon_fee),
-        }],
+        funds,
         callback_msg: EntropyCallbackData {
-            original_sender: info.sender,
+            original_sender: player,
             game: idx,
         },
     }.into_cosmos(config.entropy_beacon_addr)?];
 
-    // If there is a fee, send it to the fee address
-    if !config.fee_amount.is_zero() {
-        msgs.push(CosmosMsg::Bank(BankMsg::Send {
-            to_address: kujira::utils::fee_address().to_string(),
-            amount: config.token.coins(&config.fee_amount),
-        }))
-    };
-
-    // Transfer player bet amount to the contract address
-    let _player_deposit_msg: BankMsg = BankMsg::Send {
-        to_address: env.contract.address.to_string(),
-        amount: config.token.coins(&game.bet_size),
-    };
-
     // Response to the contract caller
     Ok(Response::new()
     .add_attribute("game", idx)
     .add_attribute("player", game.player)
+    .add_attribute("fee", fee)
     .add_messages(msgs))
 
 }
 
-// Calculate the payout amount for a given bet
-pub fn calculate_payout(bet_amount: Uint128, outcome: u8, rule_set: RuleSet) -> Uint128 {
-    match outcome {
-        0 => bet_amount * rule_set.zero,
-        1 => bet_amount * rule_set.one,
-        2 => bet_amount * rule_set.two,
-        3 => bet_amount * rule_set.three,
-        4 => bet_amount * rule_set.four,
-        5 => bet_amount * rule_set.five,
-        6 => bet_amount * rule_set.six,
-        _ => Uint128::zero(),
+// Calculate the payout amount for a given bet, stake included and rounded down
+pub fn calculate_payout(
+    bet_amount: Uint128,
+    outcome: u8,
+    wheel: &Wheel,
+) -> Result<Uint128, ContractError> {
+    let payout = match wheel.segment(outcome) {
+        Some(segment) => segment.payout,
+        None => return Ok(Uint128::zero()),
+    };
+
+    // Multiply in 256 bits so only a result that doesn't fit in a Uint128 fails,
+    // the division truncates any fraction of a unit
+    let amount = bet_amount.full_mul(payout.atomics()) / Uint256::from(Decimal::one().atomics());
+    Uint128::try_from(amount).map_err(|_| ContractError::PayoutOverflow {
+        bet_size: bet_amount,
+        payout,
+    })
+}
+
+// Split the tracked bankroll of a denom into what pending games may still need
+// and what is free to back new bets
+pub fn load_bankroll(storage: &dyn Storage, denom: &AssetInfo) -> StdResult<BankrollResponse> {
+    let bankroll = BANKROLL
+        .may_load(storage, denom.key())?
+        .unwrap_or_default();
+    let reserved = load_reserved(storage, denom)?;
+
+    // Whatever isn't explained by deposits and withdrawals was won or lost at the table,
+    // counting pending games as if they pay out in full
+    let funded = bankroll.net(reserved) + bankroll.withdrawn;
+
+    Ok(BankrollResponse {
+        denom: denom.clone(),
+        balance: bankroll.balance,
+        reserved,
+        free: bankroll.balance.saturating_sub(reserved),
+        deposited: bankroll.deposited,
+        withdrawn: bankroll.withdrawn,
+        profit: funded.saturating_sub(bankroll.deposited),
+        loss: bankroll.deposited.saturating_sub(funded),
+    })
+}
+
+// Payouts set aside in a denom for games that are still pending
+fn load_reserved(storage: &dyn Storage, denom: &AssetInfo) -> StdResult<Uint128> {
+    Ok(RESERVED
+        .may_load(storage, denom.key())?
+        .unwrap_or_default())
+}
+
+// Apply a change to the tracked bankroll of a denom
+fn update_bankroll(
+    storage: &mut dyn Storage,
+    denom: &AssetInfo,
+    update: impl FnOnce(&mut Bankroll) -> StdResult<()>,
+) -> StdResult<()> {
+    let mut bankroll = BANKROLL
+        .may_load(storage, denom.key())?
+        .unwrap_or_default();
+    update(&mut bankroll)?;
+    BANKROLL.save(storage, denom.key(), &bankroll)
+}
+
+// Largest bet in a denom whose worst case payout stays within the exposure limit
+// of its free bankroll
+pub fn max_bet(config: &Config, denom_config: &DenomConfig, free_bankroll: Uint128) -> Uint128 {
+    let allowance = free_bankroll * config.max_exposure;
+
+    // Divide by the highest multiplier of the denom's wheel, rounding down
+    let max_payout = denom_config.wheel(&config.wheel).max_payout();
+    if max_payout.is_zero() {
+        return denom_config.max_bet;
+    }
+    let max_bet = allowance.full_mul(Decimal::one().atomics()) / Uint256::from(max_payout.atomics());
+    Uint128::try_from(max_bet)
+        .unwrap_or(Uint128::MAX)
+        .min(denom_config.max_bet)
+}
+
+// Check a pull against the cooldown of the player and the wager cap of the denom,
+// and count it towards the next one
+fn apply_rate_limit(
+    storage: &mut dyn Storage,
+    rate_limit: &RateLimit,
+    denom_config: &DenomConfig,
+    player: &Addr,
+    bet_size: Uint128,
+    height: u64,
+) -> Result<(), ContractError> {
+    let mut activity = PLAYER_ACTIVITY.may_load(storage, player)?.unwrap_or_default();
+
+    if let (Some(cooldown), Some(last)) = (rate_limit.cooldown_blocks, activity.last_pull_height) {
+        let retry_height = last.saturating_add(cooldown);
+        if height < retry_height {
+            return Err(ContractError::RateLimited { retry_height });
+        }
+    }
+
+    if let Some(cap) = &denom_config.wager_cap {
+        // Windows are fixed, a new one starts with the first pull after the last ran out
+        let key = (denom_config.denom.key(), player);
+        let mut window = WAGER_WINDOWS.may_load(storage, key)?.unwrap_or_default();
+        if height >= window.start.saturating_add(cap.window_blocks) {
+            window.start = height;
+            window.wagered = Uint128::zero();
+        }
+        if window.wagered.saturating_add(bet_size) > cap.amount {
+            return Err(ContractError::RateLimited {
+                retry_height: window.start.saturating_add(cap.window_blocks),
+            });
+        }
+        window.wagered += bet_size;
+        WAGER_WINDOWS.save(storage, key, &window)?;
+    }
+
+    activity.last_pull_height = Some(height);
+    PLAYER_ACTIVITY.save(storage, player, &activity)?;
+    Ok(())
+}
+
+// Split a fee between the recipients by weight, the first one also getting what
+// is left over from rounding down
+fn accrue_fees(
+    storage: &mut dyn Storage,
+    recipients: &[FeeRecipient],
+    denom: &AssetInfo,
+    amount: Uint128,
+) -> StdResult<()> {
+    if amount.is_zero() {
+        return Ok(());
+    }
+
+    let total_weight: u64 = recipients.iter().map(|recipient| u64::from(recipient.weight)).sum();
+    let mut shares: Vec<Uint128> = recipients
+        .iter()
+        .map(|recipient| amount.multiply_ratio(recipient.weight, total_weight))
+        .collect();
+    let remainder = amount - shares.iter().sum::<Uint128>();
+    shares[0] += remainder;
+
+    for (recipient, share) in recipients.iter().zip(shares) {
+        let mut fees = FEES.may_load(storage, &recipient.address)?.unwrap_or_default();
+        match fees.iter_mut().find(|fee| &fee.denom == denom) {
+            Some(fee) => fee.amount = fee.amount.checked_add(share)?,
+            None => fees.push(AccruedFee {
+                denom: denom.clone(),
+                amount: share,
+            }),
+        }
+        FEES.save(storage, &recipient.address, &fees)?;
     }
+    Ok(())
 }
 
-// Take the entropy and return a random number between 0 and 6
-pub fn get_outcome_from_entropy(entropy: &[u8]) -> Vec<u8> {
-    // Hash the input entropy using SHA256
-    let mut hasher = Sha256::new();
-    hasher.update(entropy);
-    let hash_result = hasher.finalize();
+// Set aside the payout a pending game may need
+fn reserve_liability(storage: &mut dyn Storage, denom: &AssetInfo, amount: Uint128) -> StdResult<()> {
+    RESERVED.update(storage, denom.key(), |reserved| -> StdResult<_> {
+        Ok(reserved.unwrap_or_default().checked_add(amount)?)
+    })?;
+    Ok(())
+}
 
-    // Use the last byte of the hash as the random number
-    let random_byte = hash_result[hash_result.len() - 1];
+// Give back what was reserved for a game once it is settled or refunded
+fn release_liability(storage: &mut dyn Storage, game: &Game) -> StdResult<()> {
+    RESERVED.update(storage, game.token.key(), |reserved| -> StdResult<_> {
+        Ok(reserved.unwrap_or_default().checked_sub(game.liability)?)
+    })?;
+    Ok(())
+}
+
+// Add a settled game to the house and player statistics
+fn record_settlement(storage: &mut dyn Storage, game: &Game) -> StdResult<()> {
+    record_stats(storage, &game.token, |stats| stats.record_settlement(game))?;
+
+    let key = (game.token.key(), &game.player);
+    let mut player_stats = PLAYER_STATS.may_load(storage, key)?.unwrap_or_default();
+    player_stats.record_settlement(game);
+    PLAYER_STATS.save(storage, key, &player_stats)
+}
+
+// Apply an update to the house statistics of a denom
+fn record_stats(
+    storage: &mut dyn Storage,
+    denom: &AssetInfo,
+    update: impl FnOnce(&mut HouseStats),
+) -> StdResult<()> {
+    let mut stats = STATS.may_load(storage, denom.key())?.unwrap_or_default();
+    update(&mut stats);
+    STATS.save(storage, denom.key(), &stats)
+}
 
-    // Map the random byte to a number between 0 and 6
-    let outcome = random_byte % 7;
-    vec![outcome]
+// Take the entropy and return the segment it lands on, each segment being as
+// likely as the share of slots it takes up on the wheel
+pub fn get_segment_from_entropy(entropy: &[u8], wheel: &Wheel) -> u8 {
+    let slot = get_outcome_from_entropy(entropy, wheel.total_weight());
+    wheel.segment_at(slot)
+}
+
+// Take the entropy and return a uniformly distributed number in [0, wheel_size).
+// The wheel size must be greater than zero.
+pub fn get_outcome_from_entropy(entropy: &[u8], wheel_size: u64) -> u64 {
+    let wheel_size = BigUint::from(wheel_size);
+    let mut entropy = entropy.to_vec();
+
+    loop {
+        // Read all of the entropy as one big-endian integer
+        let value = BigUint::from_bytes_be(&entropy);
+
+        // Only accept values below the largest multiple of the wheel size that fits
+        // in the entropy, so that every outcome is backed by exactly as many values
+        let range = BigUint::one() << (entropy.len() * 8);
+        let limit = &range - (&range % &wheel_size);
+        if value < limit {
+            return (value % &wheel_size).to_u64().unwrap_or_default();
+        }
+
+        // Practically unreachable with 64 bytes of entropy: rehash and try again
+        entropy = Sha512::digest(&entropy).to_vec();
+    }
 }
@@ -1,4 +1,6 @@
-use cosmwasm_std::StdError;
+use crate::state::GameStatus;
+use cosmwasm_std::{Decimal, StdError, Uint128};
+use cw_utils::Expiration;
 use cw_utils::PaymentError;
 // use serde::{Deserialize, Serialize};
 use thiserror::Error;
@@ -14,17 +16,46 @@ pub enum ContractError {
     #[error("Unauthorized")]
     Unauthorized {},
 
-    #[error("Insufficient funds")]
-    InsufficientFunds {},
+    #[error("Contract is paused, no new games can be started")]
+    Paused {},
+
+    #[error("The entropy fee of {fee} is not covered by the free {denom} bankroll of {free}")]
+    BeaconFeeNotCovered {
+        denom: String,
+        fee: Uint128,
+        free: Uint128,
+    },
+
+    #[error("Insufficient shares: {available} available")]
+    InsufficientShares { available: Uint128 },
+
+    #[error("Bankroll has nothing left to its {shares} outstanding shares: they have to be withdrawn, or pending games settled, before new deposits")]
+    BankrollWipedOut { shares: Uint128 },
+
+    #[error("Deposit is too small to buy a single share")]
+    DepositTooSmall {},
+
+    #[error("Nothing has finished unbonding yet")]
+    NothingToWithdraw {},
+
+    #[error("No ownership transfer is pending")]
+    NoPendingOwner {},
+
+    #[error("Ownership transfer has expired")]
+    OwnershipTransferExpired {},
 
     #[error("Invalid Token")]
     InvalidToken {},
 
-    #[error("Invalid bet")]
-    InvalidBet {},
+    #[error("Denom {denom} is not whitelisted")]
+    UnknownDenom { denom: String },
 
-    #[error("Already paid out")]
-    AlreadyPaidOut {},
+    #[error("Game {game_id} cannot move from {from} to {to}")]
+    InvalidGameTransition {
+        game_id: Uint128,
+        from: GameStatus,
+        to: GameStatus,
+    },
 
     #[error("Invalid bet number")]
     InvalidBetNumber {},
@@ -38,6 +69,63 @@ pub enum ContractError {
     #[error("Callback was not called by beacon, but by someone else")]
     InvalidEntropyCallback {},
 
+    #[error("Invalid denom: must not be empty")]
+    InvalidDenom {},
+
+    #[error("Payout of {payout}x on a bet of {bet_size} overflows")]
+    PayoutOverflow { bet_size: Uint128, payout: Decimal },
+
+    #[error("House edge too low: the wheel returns {rtp} to the player, the minimum house edge is {min_house_edge}")]
+    HouseEdgeTooLow { rtp: Decimal, min_house_edge: Decimal },
+
+    #[error("Invalid wheel: {reason}")]
+    InvalidWheel { reason: String },
+
+    #[error("Invalid bet limits: min_bet ({min_bet}) must be non-zero and not exceed max_bet ({max_bet})")]
+    InvalidBetLimits { min_bet: Uint128, max_bet: Uint128 },
+
+    #[error("Invalid rate limit: cooldown, window and wager cap must be greater than zero when set")]
+    InvalidRateLimit {},
+
+    #[error("Bet too small: the minimum bet is {min_bet}")]
+    BetTooSmall { min_bet: Uint128 },
+
+    #[error("Bet too large: the maximum bet is {max_bet}")]
+    BetTooLarge { max_bet: Uint128 },
+
+    #[error("Rate limited: try again from height {retry_height}")]
+    RateLimited { retry_height: u64 },
+
+    #[error("Invalid fee: fee_amount ({fee_amount}) must be lower than min_bet ({min_bet})")]
+    InvalidFee { fee_amount: Uint128, min_bet: Uint128 },
+
+    #[error("Invalid fee: {bps} basis points must be lower than 10000")]
+    InvalidFeeBps { bps: u16 },
+
+    #[error("Invalid fee recipients: there must be at least one and every weight must be greater than zero")]
+    InvalidFeeRecipients {},
+
+    #[error("Stake of {stake} does not cover the fee of {fee}")]
+    StakeDoesNotCoverFee { stake: Uint128, fee: Uint128 },
+
+    #[error("No fees to claim")]
+    NothingToClaim {},
+
+    #[error("Invalid entropy timeout: must be greater than zero")]
+    InvalidTimeout {},
+
+    #[error("Invalid callback gas limit: must be greater than zero")]
+    InvalidCallbackGasLimit {},
+
+    #[error("Invalid max exposure: must be greater than zero and at most one")]
+    InvalidMaxExposure {},
+
+    #[error("Game {game_id} is still waiting for entropy until {expires}")]
+    GameNotExpired { game_id: Uint128, expires: Expiration },
+
+    #[error("Cannot migrate from {contract} {version}: its storage layout is not compatible")]
+    CannotMigrate { contract: String, version: String },
+
     #[error("Original requester for entropy is not trusted (must be the contract itself)")]
     InvalidEntropyRequester {},
 }
@@ -1,20 +1,43 @@
-use crate::state::RuleSet;
+use crate::state::{AccruedFee, AssetInfo, DenomConfig, FeeRecipient, Game, GameStatus, HouseStats, RateLimit, Unbonding, Wheel};
 use cosmwasm_schema::{cw_serde, QueryResponses};
-use cosmwasm_std::{Addr, /*Api, Coin, StdResult,*/ Uint128};
-// use cw20::{Cw20Coin, Cw20ReceiveMsg};
+use cosmwasm_std::{Addr, /*Api, Coin, StdResult,*/ Decimal, Order, Uint128};
+use cw20::Cw20ReceiveMsg;
+use cw_utils::{Duration, Expiration};
 use entropy_beacon_cosmos::EntropyCallbackMsg;
-// use kujira::denom::Denom;
+use kujira::denom::Denom;
 // use serde::{Deserialize, Serialize};
 
 #[cw_serde]
 pub struct InstantiateMsg {
-    // pub entropy_beacon_addr: Addr,
-    // pub owner_addr: Addr,
-    // pub token: Denom,
-    // pub play_amount: Uint128,
-    // pub win_amount: Uint128,
-    // pub fee_amount: Uint128,
-    // pub rule_set: RuleSet,
+    /// Address of the entropy beacon for the target chain
+    pub entropy_beacon_addr: String,
+    /// Address allowed to administer the contract
+    pub owner_addr: String,
+    /// Address allowed to pause new games next to the owner
+    pub guardian_addr: Option<String>,
+    /// Denoms that bets can be placed in, each with its own limits, fee model and optional wheel
+    pub denoms: Vec<DenomConfig>,
+    /// Segments of the default wheel with their label, weight and payout ratio
+    pub wheel: Wheel,
+    /// Share of every bet the wheel must keep for the house, checked against its best bet
+    pub min_house_edge: Decimal,
+    /// Share of the free bankroll that the worst case payout of a single bet may take
+    pub max_exposure: Decimal,
+    /// Native denom the entropy beacon charges its fee in. A non-zero fee is paid out of
+    /// the free bankroll of this denom, so it has to be whitelisted and funded. The fee
+    /// is paid for games in every denom, so the liquidity providers of this denom carry
+    /// the entropy cost of the other denoms' games as well.
+    pub beacon_fee_denom: Denom,
+    /// Addresses protocol fees accrue to, split by weight
+    pub fee_recipients: Vec<FeeRecipient>,
+    /// Optional cooldown per address
+    pub rate_limit: RateLimit,
+    /// How long a game waits for entropy before the bet can be refunded
+    pub entropy_timeout: Duration,
+    /// Gas provisioned for the beacon's entropy callback
+    pub callback_gas_limit: u64,
+    /// How long liquidity providers wait between unbonding and withdrawing
+    pub unbonding_period: Duration,
 }
 
 #[cw_serde]
@@ -24,51 +47,370 @@ pub struct EntropyCallbackData {
 }
 
 #[cw_serde]
+#[allow(clippy::large_enum_variant)]
 pub enum ExecuteMsg {
     Pull {
         // player_bet_amount: Uint128,
         bet_number: Uint128,
     },
 
+    /// Entry point for whitelisted cw20 tokens, the inner message is a [`ReceiveMsg`]
+    Receive(Cw20ReceiveMsg),
+
     ReceiveEntropy(EntropyCallbackMsg),
 
-    Spin {
-        // player_bet_amount: Uint128,
-        bet_number: Uint128,
+    /// Return the bet of a game whose entropy did not arrive before the timeout.
+    /// Anyone may call this, the stake always goes back to the player.
+    Refund { game_id: Uint128 },
+
+    /// Owner only: change any part of the config, fields left empty are kept
+    UpdateConfig(UpdateConfigMsg),
+
+    /// Send the sender every fee accrued to it, in each denom
+    ClaimFees {},
+
+    /// Owner only: whitelist a denom for bets, or replace the terms of one that is
+    AddDenom(DenomConfig),
+
+    /// Owner only: stop taking bets in a denom. Its bankroll, shares and pending
+    /// games are left as they are, so they can still be settled and withdrawn.
+    RemoveDenom { denom: AssetInfo },
+
+    /// Owner only: offer ownership to a new address, optionally until `expiry`
+    ProposeOwner {
+        new_owner: String,
+        expiry: Option<Expiration>,
     },
+
+    /// Pending owner only: complete the ownership transfer
+    AcceptOwnership {},
+
+    /// Owner only: withdraw a pending ownership transfer
+    CancelOwnershipTransfer {},
+
+    /// Owner only: give up ownership for good, leaving the contract without an owner
+    RenounceOwnership {},
+
+    /// Owner only: set or remove the guardian
+    SetGuardian { guardian_addr: Option<String> },
+
+    /// Owner or guardian: stop accepting new pulls
+    Pause {},
+
+    /// Owner only: accept new pulls again
+    Unpause {},
+
+    /// Owner only: add the attached funds to the house bankroll, for shares like any provider.
+    /// cw20 tokens are deposited through [`ReceiveMsg::DepositBankroll`]. The owner's shares
+    /// are taken out through [`ExecuteMsg::UnbondLiquidity`] like everyone else's.
+    DepositBankroll {},
+
+    /// Add the attached funds to the bankroll in exchange for shares
+    DepositLiquidity {},
+
+    /// Start the unbonding period for some of the sender's shares
+    UnbondLiquidity { denom: AssetInfo, shares: Uint128 },
+
+    /// Redeem every unbonded share of the sender at the current share price
+    WithdrawLiquidity {},
+}
+
+/// What cw20 tokens sent to the contract are for. Each does the same as the
+/// [`ExecuteMsg`] of the same name, on behalf of whoever sent the tokens.
+#[cw_serde]
+pub enum ReceiveMsg {
+    Pull { bet_number: Uint128 },
+    DepositBankroll {},
+    DepositLiquidity {},
+}
+
+#[cw_serde]
+#[derive(Default)]
+pub struct UpdateConfigMsg {
+    pub entropy_beacon_addr: Option<String>,
+    /// Replaces the default wheel
+    pub wheel: Option<Wheel>,
+    pub min_house_edge: Option<Decimal>,
+    pub max_exposure: Option<Decimal>,
+    pub beacon_fee_denom: Option<Denom>,
+    /// Replaces the fee recipients, fees accrued so far stay with their recipient
+    pub fee_recipients: Option<Vec<FeeRecipient>>,
+    pub rate_limit: Option<RateLimit>,
+    pub entropy_timeout: Option<Duration>,
+    pub callback_gas_limit: Option<u64>,
+    pub unbonding_period: Option<Duration>,
 }
 
 #[cw_serde]
 #[derive(QueryResponses)]
 pub enum QueryMsg {
+    #[returns(ConfigResponse)]
+    Config {},
+
     #[returns(GameResponse)]
     Game { idx: Uint128 },
+
+    /// A player's games, newest first unless `order` says otherwise
+    #[returns(GamesResponse)]
+    GamesByPlayer {
+        player: String,
+        start_after: Option<Uint128>,
+        limit: Option<u32>,
+        order: Option<OrderBy>,
+    },
+
+    /// All games in the order they were started
+    #[returns(GamesResponse)]
+    Games {
+        start_after: Option<Uint128>,
+        limit: Option<u32>,
+    },
+
+    /// House results per denom, with realised and theoretical return to player
+    #[returns(StatsResponse)]
+    Stats {},
+
+    /// Terms of a whitelisted denom
+    #[returns(DenomConfig)]
+    Denom { denom: AssetInfo },
+
+    /// Every whitelisted denom
+    #[returns(DenomsResponse)]
+    Denoms {},
+
+    /// Chance and payout of every segment, with the return to player of the wheel
+    /// bets in the denom are placed on
+    #[returns(OddsResponse)]
+    Odds { denom: AssetInfo },
+
+    /// The tracked bankroll of a denom, split into what pending games may need and what is free
+    #[returns(BankrollResponse)]
+    Bankroll { denom: AssetInfo },
+
+    /// Share price and total shares of the bankroll of a denom
+    #[returns(VaultResponse)]
+    Vault { denom: AssetInfo },
+
+    /// Shares of a liquidity provider in a denom, bonded and unbonding
+    #[returns(PositionResponse)]
+    Position { address: String, denom: AssetInfo },
+
+    /// Largest bet accepted in a denom right now, given the bankroll and pending games
+    #[returns(MaxBetResponse)]
+    MaxBet { denom: AssetInfo },
+
+    /// Fees accrued to an address that have not been claimed yet
+    #[returns(AccruedFeesResponse)]
+    AccruedFees { address: String },
+
+    /// Lifetime record of a single player in a denom
+    #[returns(PlayerStatsResponse)]
+    PlayerStats { address: String, denom: AssetInfo },
+
+    #[returns(OwnershipResponse)]
+    Ownership {},
+
+    #[returns(PauseStatusResponse)]
+    PauseStatus {},
+}
+
+#[cw_serde]
+pub struct ConfigResponse {
+    pub entropy_beacon_addr: Addr,
+    pub owner: Option<Addr>,
+    pub guardian: Option<Addr>,
+    pub wheel: Wheel,
+    pub min_house_edge: Decimal,
+    pub max_exposure: Decimal,
+    pub beacon_fee_denom: Denom,
+    pub fee_recipients: Vec<FeeRecipient>,
+    pub rate_limit: RateLimit,
+    pub entropy_timeout: Duration,
+    pub callback_gas_limit: u64,
+    pub unbonding_period: Duration,
+}
+
+#[cw_serde]
+pub struct AccruedFeesResponse {
+    pub address: Addr,
+    pub fees: Vec<AccruedFee>,
+}
+
+#[cw_serde]
+pub struct DenomsResponse {
+    pub denoms: Vec<DenomConfig>,
+}
+
+#[cw_serde]
+pub struct OwnershipResponse {
+    pub owner: Option<Addr>,
+    pub pending_owner: Option<Addr>,
+    pub pending_expiry: Option<Expiration>,
+}
+
+#[cw_serde]
+pub struct PauseStatusResponse {
+    pub paused: bool,
+    pub guardian: Option<Addr>,
 }
 
 #[cw_serde]
 pub struct GameResponse {
     pub idx: Uint128,
     pub player: Addr,
-    pub result: Option<Vec<u8>>,
-    pub win: bool,
-}
-
-impl ExecuteMsg {
-    pub fn calculate_payout(bet_amount: Uint128, result: u8, rule_set: RuleSet) -> Uint128 {
-        match result {
-            0 => bet_amount * rule_set.zero,
-            1 => bet_amount * rule_set.one,
-            2 => bet_amount * rule_set.two,
-            3 => bet_amount * rule_set.three,
-            4 => bet_amount * rule_set.four,
-            5 => bet_amount * rule_set.five,
-            6 => bet_amount * rule_set.six,
-            _ => Uint128::zero(),
+    pub bet_number: Uint128,
+    pub bet_size: Uint128,
+    pub token: AssetInfo,
+    pub outcome: Option<u8>,
+    pub payout: Uint128,
+    /// Protocol fee charged so far, from the stake and from the payout
+    pub fee: Uint128,
+    pub status: GameStatus,
+    pub expires: Expiration,
+}
+
+impl From<Game> for GameResponse {
+    fn from(game: Game) -> Self {
+        GameResponse {
+            idx: game.game_id,
+            player: game.player,
+            bet_number: game.bet_number,
+            bet_size: game.bet_size,
+            token: game.token,
+            outcome: game.outcome,
+            payout: game.payout,
+            fee: game.fee,
+            status: game.status,
+            expires: game.expires,
         }
     }
 }
 
 #[cw_serde]
-pub struct MigrateMsg {
-    pub fee_amount: Uint128,
+pub struct GamesResponse {
+    pub games: Vec<GameResponse>,
+}
+
+#[cw_serde]
+pub struct StatsResponse {
+    pub stats: Vec<DenomStats>,
+}
+
+#[cw_serde]
+pub struct DenomStats {
+    /// Denom of the coin or contract of the cw20 token
+    pub denom: String,
+    pub stats: HouseStats,
+    /// Paid out divided by wagered over all settled games
+    pub realised_rtp: Decimal,
+    /// Expected return of a bet on each wheel number of the wheel the denom is played on,
+    /// empty once the denom is no longer whitelisted
+    pub theoretical_rtp: Vec<Decimal>,
+}
+
+#[cw_serde]
+pub struct BankrollResponse {
+    pub denom: AssetInfo,
+    /// Everything the house holds in the bet denom
+    pub balance: Uint128,
+    /// Payouts set aside for games that are still pending
+    pub reserved: Uint128,
+    /// What is left to back new bets or withdraw
+    pub free: Uint128,
+    /// Deposits by the owner and liquidity providers so far, including the funds
+    /// sent at instantiation
+    pub deposited: Uint128,
+    /// Liquidity redeemed so far, the owner's included
+    pub withdrawn: Uint128,
+    /// Result of play so far, net of the entropy fees the bankroll paid and counting
+    /// pending games as if they pay out in full. It is shared by all share holders
+    /// through the share price. For the beacon fee denom those fees include the ones
+    /// paid for games in other denoms, see the vault's `entropy_fees`.
+    pub profit: Uint128,
+    pub loss: Uint128,
+}
+
+#[cw_serde]
+pub struct VaultResponse {
+    pub denom: AssetInfo,
+    pub total_shares: Uint128,
+    /// Value of one share in the bet denom, leaving out what pending games have reserved
+    pub share_price: Decimal,
+    /// Whether this bankroll pays the entropy fee of every game, in whatever denom
+    pub pays_entropy_fees: bool,
+    /// Entropy fees paid out of this bankroll so far, already taken out of the share price
+    pub entropy_fees: Uint128,
+    pub unbonding_period: Duration,
 }
+
+#[cw_serde]
+pub struct PositionResponse {
+    pub address: Addr,
+    pub shares: Uint128,
+    /// What the bonded shares are worth right now
+    pub value: Uint128,
+    pub unbonding: Vec<Unbonding>,
+}
+
+#[cw_serde]
+pub struct MaxBetResponse {
+    pub denom: AssetInfo,
+    pub max_bet: Uint128,
+}
+
+#[cw_serde]
+pub struct OddsResponse {
+    pub segments: Vec<SegmentOdds>,
+    /// Expected return per unit staked on the best bet on the wheel
+    pub rtp: Decimal,
+    /// Share of every bet kept by the house on the best bet, zero if the wheel favours players
+    pub house_edge: Decimal,
+}
+
+#[cw_serde]
+pub struct SegmentOdds {
+    pub label: String,
+    pub weight: u32,
+    pub probability: Decimal,
+    /// Multiple of the bet paid out, stake included
+    pub payout: Decimal,
+    /// Expected return per unit staked on this segment
+    pub rtp: Decimal,
+}
+
+#[cw_serde]
+pub struct PlayerStatsResponse {
+    pub address: Addr,
+    pub denom: AssetInfo,
+    pub games_played: u64,
+    pub total_wagered: Uint128,
+    pub total_won: Uint128,
+    /// How far total_won exceeds total_wagered, zero when the player is behind
+    pub net_gain: Uint128,
+    /// How far total_wagered exceeds total_won, zero when the player is ahead
+    pub net_loss: Uint128,
+    pub biggest_win: Uint128,
+    pub current_win_streak: u32,
+    pub current_lose_streak: u32,
+    pub best_win_streak: u32,
+    pub best_lose_streak: u32,
+}
+
+#[cw_serde]
+pub enum OrderBy {
+    Ascending,
+    Descending,
+}
+
+impl From<OrderBy> for Order {
+    fn from(order: OrderBy) -> Self {
+        match order {
+            OrderBy::Ascending => Order::Ascending,
+            OrderBy::Descending => Order::Descending,
+        }
+    }
+}
+
+/// Moves a contract to new code on the same storage layout, older versions are rejected
+#[cw_serde]
+pub struct MigrateMsg {}
@@ -1,18 +1,192 @@
+use std::fmt;
+
 use cosmwasm_schema::cw_serde;
 use kujira::denom::Denom;
 
-use cosmwasm_std::{Addr, Coin, Uint128};
-use cw_storage_plus::{Item, Map};
+use crate::error::ContractError;
+
+use cosmwasm_std::{
+    to_binary, Addr, BankMsg, CosmosMsg, Decimal, OverflowError, StdError, StdResult, Uint128,
+    WasmMsg,
+};
+use cw20::Cw20ExecuteMsg;
+use cw_storage_plus::{Index, IndexList, IndexedMap, Item, Map, MultiIndex};
+use cw_utils::{Duration, Expiration};
 
 #[cw_serde]
 #[serde(rename_all = "snake_case")]
 pub struct Config {
     pub entropy_beacon_addr: Addr,
-    pub owner_addr: Addr,
-    pub house_bankroll: Coin, 
-    pub fee_amount: Uint128,
-    pub rule_set: RuleSet,
-    pub token: Denom,
+    // None once ownership has been renounced
+    pub owner_addr: Option<Addr>,
+    // Optional address that may pause new games next to the owner
+    pub guardian_addr: Option<Addr>,
+    // Wheel of every whitelisted denom that doesn't bring its own
+    pub wheel: Wheel,
+    // Share of every bet the wheel must keep for the house on average
+    pub min_house_edge: Decimal,
+    // Share of the free bankroll that the worst case payout of a single bet may take
+    pub max_exposure: Decimal,
+    // Native denom the entropy beacon charges in, paid out of the bankroll of that denom
+    // for every game, whatever the game's own bet denom
+    pub beacon_fee_denom: Denom,
+    // Addresses fees are accrued to, split by weight
+    pub fee_recipients: Vec<FeeRecipient>,
+    pub rate_limit: RateLimit,
+    pub entropy_timeout: Duration,
+    pub callback_gas_limit: u64,
+    // How long liquidity providers wait between unbonding and withdrawing
+    pub unbonding_period: Duration,
+}
+
+// What bets are placed and paid out in: a native or IBC coin, or a cw20 token
+#[cw_serde]
+pub enum AssetInfo {
+    Native(Denom),
+    Cw20(Addr),
+}
+
+impl AssetInfo {
+    // Key the asset is stored under: the denom of a coin, the contract of a token
+    pub fn key(&self) -> &str {
+        match self {
+            AssetInfo::Native(denom) => denom.as_ref(),
+            AssetInfo::Cw20(contract) => contract.as_str(),
+        }
+    }
+
+    // Message sending an amount of the asset from the contract to a recipient
+    pub fn transfer_msg(&self, recipient: &Addr, amount: Uint128) -> StdResult<CosmosMsg> {
+        Ok(match self {
+            AssetInfo::Native(denom) => BankMsg::Send {
+                to_address: recipient.to_string(),
+                amount: denom.coins(&amount),
+            }
+            .into(),
+            AssetInfo::Cw20(contract) => WasmMsg::Execute {
+                contract_addr: contract.to_string(),
+                msg: to_binary(&Cw20ExecuteMsg::Transfer {
+                    recipient: recipient.to_string(),
+                    amount,
+                })?,
+                funds: vec![],
+            }
+            .into(),
+        })
+    }
+}
+
+impl fmt::Display for AssetInfo {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.key())
+    }
+}
+
+// Terms a whitelisted denom is played on, amounts are in that denom
+#[cw_serde]
+#[serde(rename_all = "snake_case")]
+pub struct DenomConfig {
+    pub denom: AssetInfo,
+    pub min_bet: Uint128,
+    pub max_bet: Uint128,
+    pub fee: FeeModel,
+    // Own payout table, the default wheel of the config is used when not set
+    pub wheel: Option<Wheel>,
+    // Most an address can wager in this denom within a window of blocks
+    pub wager_cap: Option<WagerCap>,
+}
+
+impl DenomConfig {
+    pub fn wheel<'a>(&'a self, default: &'a Wheel) -> &'a Wheel {
+        self.wheel.as_ref().unwrap_or(default)
+    }
+}
+
+// How the protocol fee of a bet is worked out
+#[cw_serde]
+pub enum FeeModel {
+    // Fixed amount taken out of every stake
+    Flat(Uint128),
+    // Share of every stake, in basis points
+    WagerBps(u16),
+    // Share of what a winning payout adds to the wager, in basis points
+    NetWinningsBps(u16),
+}
+
+// Written to event attributes, e.g. `flat:5` or `wager_bps:150`
+impl fmt::Display for FeeModel {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FeeModel::Flat(amount) => write!(f, "flat:{}", amount),
+            FeeModel::WagerBps(bps) => write!(f, "wager_bps:{}", bps),
+            FeeModel::NetWinningsBps(bps) => write!(f, "net_winnings_bps:{}", bps),
+        }
+    }
+}
+
+impl FeeModel {
+    pub const MAX_BPS: u16 = 10_000;
+
+    // Fee taken out of the stake when the bet is placed, rounded down
+    pub fn stake_fee(&self, stake: Uint128) -> Uint128 {
+        match self {
+            FeeModel::Flat(amount) => *amount,
+            FeeModel::WagerBps(bps) => stake.multiply_ratio(*bps, Self::MAX_BPS),
+            FeeModel::NetWinningsBps(_) => Uint128::zero(),
+        }
+    }
+
+    // Fee taken out of the payout when the bet is settled, rounded down
+    pub fn winnings_fee(&self, wager: Uint128, payout: Uint128) -> Uint128 {
+        match self {
+            FeeModel::NetWinningsBps(bps) => {
+                payout.saturating_sub(wager).multiply_ratio(*bps, Self::MAX_BPS)
+            }
+            _ => Uint128::zero(),
+        }
+    }
+}
+
+#[cw_serde]
+pub struct FeeRecipient {
+    pub address: Addr,
+    pub weight: u32,
+}
+
+// Fees accrued to a recipient in one denom and not claimed yet
+#[cw_serde]
+pub struct AccruedFee {
+    pub denom: AssetInfo,
+    pub amount: Uint128,
+}
+
+// Limits on how often a single address can play, whatever the denom
+#[cw_serde]
+#[derive(Default)]
+pub struct RateLimit {
+    // Blocks an address has to wait between two pulls
+    pub cooldown_blocks: Option<u64>,
+}
+
+#[cw_serde]
+pub struct WagerCap {
+    pub window_blocks: u64,
+    pub amount: Uint128,
+}
+
+// Recent pulls of an address, as far as the cooldown needs to know
+#[cw_serde]
+#[derive(Default)]
+pub struct PlayerActivity {
+    pub last_pull_height: Option<u64>,
+}
+
+// What an address wagered in one denom during the current wager cap window
+#[cw_serde]
+#[derive(Default)]
+pub struct WagerWindow {
+    pub start: u64,
+    pub wagered: Uint128,
 }
 
 #[cw_serde]
@@ -20,39 +194,204 @@ pub struct Config {
 pub struct Game {
     pub player: Addr,
     pub bet_number: Uint128,
+    // What was wagered, after the fee was taken out of the stake
     pub bet_size: Uint128, 
+    pub token: AssetInfo,
+    // Wheel the bet was placed on, so later changes can't alter a pending game
+    pub wheel: Wheel,
     pub payout: Uint128,
-    pub result: Option<Vec<u8>>,
-    pub played: bool,
-    pub win: Option<bool>,
+    // Fee taken from the stake, plus any taken from the payout once settled
+    pub fee: Uint128,
+    // Fee model the bet was placed under
+    pub fee_model: FeeModel,
+    // Reserved from the bankroll while the game is pending: the payout, or the wager
+    // when a refund would return more
+    pub liability: Uint128,
+    pub outcome: Option<u8>,
+    pub status: GameStatus,
     pub game_id: Uint128, 
+    pub expires: Expiration,
 }
 
 impl Game {
     // Cheks if player bet matches the outcome generated by the entropy beacon
-    pub fn win(&self, player_bet: Uint128) -> bool {
-            // Convert the result vector to a vector of u128 values
-            let result: Vec<u128> = self.result.clone().unwrap_or_default().into_iter().map(|x| u128::from(x)).collect();
+    pub fn win(&self) -> bool {
+        self.outcome.map(u128::from) == Some(self.bet_number.u128())
+    }
+
+    // Moves the game to its next status, rejecting anything out of order
+    pub fn transition(&mut self, to: GameStatus) -> Result<(), ContractError> {
+        if !self.status.can_transition_to(&to) {
+            return Err(ContractError::InvalidGameTransition {
+                game_id: self.game_id,
+                from: self.status.clone(),
+                to,
+            });
+        }
+        self.status = to;
+        Ok(())
+    }
+}
 
-            // Convert the player_bet Uint128 to a u128 value
-            let player_bet_u128 = player_bet.u128();
+/// Lifecycle of a game, from the pull until the bet is settled
+#[cw_serde]
+pub enum GameStatus {
+    /// The bet is placed and the game waits for the beacon callback
+    AwaitingEntropy,
+    /// The wheel has spun and the player lost, nothing is owed
+    Resolved,
+    /// The wheel has spun and the player's winnings were sent
+    PaidOut,
+    /// The bet was returned because the entropy never arrived in time
+    Refunded,
+    /// The entropy arrived after the timeout and was ignored, awaiting refund
+    Expired,
+}
+
+impl GameStatus {
+    pub fn can_transition_to(&self, to: &GameStatus) -> bool {
+        matches!(
+            (self, to),
+            (GameStatus::AwaitingEntropy, GameStatus::Resolved)
+                | (GameStatus::AwaitingEntropy, GameStatus::PaidOut)
+                | (GameStatus::AwaitingEntropy, GameStatus::Refunded)
+                | (GameStatus::AwaitingEntropy, GameStatus::Expired)
+                | (GameStatus::Expired, GameStatus::Refunded)
+        )
+    }
+}
 
-            // Check if the player bet matches the outcome generated by the entropy beacon
-            result.contains(&player_bet_u128)
+impl fmt::Display for GameStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            GameStatus::AwaitingEntropy => write!(f, "awaiting_entropy"),
+            GameStatus::Resolved => write!(f, "resolved"),
+            GameStatus::PaidOut => write!(f, "paid_out"),
+            GameStatus::Refunded => write!(f, "refunded"),
+            GameStatus::Expired => write!(f, "expired"),
         }
     }
+}
 
+// One segment of the wheel that players can bet on
+#[cw_serde]
+#[serde(rename_all = "snake_case")]
+pub struct Segment {
+    pub label: String,
+    // Number of slots the segment takes up on the wheel
+    pub weight: u32,
+    // Multiple of the bet paid out when the segment comes up. The stake is included,
+    // so 1.9 returns the bet plus 0.9 of it and 0.5 hands back half the bet.
+    // Payouts are rounded down to a whole unit, in the house's favour.
+    pub payout: Decimal,
+}
 
+// The wheel, numbered by segment position starting at zero
 #[cw_serde]
 #[serde(rename_all = "snake_case")]
-pub struct RuleSet {
-    pub zero: Uint128,
-    pub one: Uint128,
-    pub two: Uint128,
-    pub three: Uint128,
-    pub four: Uint128,
-    pub five: Uint128,
-    pub six: Uint128,
+pub struct Wheel {
+    pub segments: Vec<Segment>,
+}
+
+impl Wheel {
+    // Outcomes are stored as u8, so that caps the number of segments
+    pub const MAX_SEGMENTS: usize = u8::MAX as usize + 1;
+
+    pub fn segment(&self, number: u8) -> Option<&Segment> {
+        self.segments.get(number as usize)
+    }
+
+    // Total number of slots on the wheel
+    pub fn total_weight(&self) -> u64 {
+        self.segments.iter().map(|segment| u64::from(segment.weight)).sum()
+    }
+
+    // Chance of the wheel landing on a segment
+    pub fn probability(&self, segment: &Segment) -> Decimal {
+        Decimal::from_ratio(segment.weight, self.total_weight())
+    }
+
+    // Expected return per unit staked on each segment
+    pub fn segment_rtp(&self) -> Result<Vec<Decimal>, OverflowError> {
+        self.segments
+            .iter()
+            .map(|segment| segment.payout.checked_mul(self.probability(segment)))
+            .collect()
+    }
+
+    // Highest payout multiplier on the wheel
+    pub fn max_payout(&self) -> Decimal {
+        self.segments
+            .iter()
+            .map(|segment| segment.payout)
+            .max()
+            .unwrap_or_default()
+    }
+
+    // Return to player of the best bet on the wheel, which is what the house
+    // has to price against since players choose their segment
+    pub fn rtp(&self) -> Result<Decimal, OverflowError> {
+        Ok(self.segment_rtp()?.into_iter().max().unwrap_or_default())
+    }
+
+    // Segment that covers a slot in [0, total_weight)
+    pub fn segment_at(&self, slot: u64) -> u8 {
+        let mut end = 0u64;
+        for (number, segment) in self.segments.iter().enumerate() {
+            end += u64::from(segment.weight);
+            if slot < end {
+                return number as u8;
+            }
+        }
+        (self.segments.len() - 1) as u8
+    }
+}
+
+// Running totals of the house's results in one denom
+#[cw_serde]
+#[derive(Default)]
+pub struct HouseStats {
+    pub games_settled: u64,
+    pub games_won: u64,
+    pub games_refunded: u64,
+    pub total_wagered: Uint128,
+    pub total_paid_out: Uint128,
+    pub total_refunded: Uint128,
+    pub fees_collected: Uint128,
+    // Indexed by wheel number: bets placed on it, bets on it that won, times it came up
+    pub bets_per_number: Vec<u64>,
+    pub wins_per_number: Vec<u64>,
+    pub hits_per_number: Vec<u64>,
+}
+
+impl HouseStats {
+    pub fn record_settlement(&mut self, game: &Game) {
+        let bet_number = game.bet_number.u128() as usize;
+        self.games_settled += 1;
+        self.total_wagered += game.bet_size;
+        self.total_paid_out += game.payout;
+        self.fees_collected += game.fee;
+        Self::count(&mut self.bets_per_number, bet_number);
+        if let Some(outcome) = game.outcome {
+            Self::count(&mut self.hits_per_number, outcome as usize);
+        }
+        if game.win() {
+            self.games_won += 1;
+            Self::count(&mut self.wins_per_number, bet_number);
+        }
+    }
+
+    pub fn record_refund(&mut self, game: &Game) {
+        self.games_refunded += 1;
+        self.total_refunded += game.bet_size + game.fee;
+    }
+
+    fn count(counts: &mut Vec<u64>, number: usize) {
+        if counts.len() <= number {
+            counts.resize(number + 1, 0);
+        }
+        counts[number] += 1;
+    }
 }
 
 #[cw_serde]
@@ -61,6 +400,189 @@ pub struct EntropyCallbackData {
     pub original_sender: Addr,
 }
 
+// Next free game id, handed out to each new pull
 pub const IDX: Item<Uint128> = Item::new("idx");
-pub const GAME: Map<u128, Game> = Map::new("game");
+
+// Games are stored by id and indexed by player for per-player history
+pub struct GameIndexes<'a> {
+    pub player: MultiIndex<'a, Addr, Game, u128>,
+}
+
+impl<'a> IndexList<Game> for GameIndexes<'a> {
+    fn get_indexes(&'_ self) -> Box<dyn Iterator<Item = &'_ dyn Index<Game>> + '_> {
+        let v: Vec<&dyn Index<Game>> = vec![&self.player];
+        Box::new(v.into_iter())
+    }
+}
+
+pub fn games<'a>() -> IndexedMap<'a, u128, Game, GameIndexes<'a>> {
+    let indexes = GameIndexes {
+        player: MultiIndex::new(|_pk, game| game.player.clone(), "game", "game__player"),
+    };
+    IndexedMap::new("game", indexes)
+}
 pub const CONFIG: Item<Config> = Item::new("state");
+
+// Lifetime record of a single player in one denom
+#[cw_serde]
+#[derive(Default)]
+pub struct PlayerStats {
+    pub games_played: u64,
+    pub total_wagered: Uint128,
+    pub total_won: Uint128,
+    pub biggest_win: Uint128,
+    pub current_win_streak: u32,
+    pub current_lose_streak: u32,
+    pub best_win_streak: u32,
+    pub best_lose_streak: u32,
+}
+
+impl PlayerStats {
+    pub fn record_settlement(&mut self, game: &Game) {
+        self.games_played += 1;
+        self.total_wagered += game.bet_size;
+        self.total_won += game.payout;
+        self.biggest_win = self.biggest_win.max(game.payout);
+
+        if game.win() {
+            self.current_win_streak += 1;
+            self.current_lose_streak = 0;
+            self.best_win_streak = self.best_win_streak.max(self.current_win_streak);
+        } else {
+            self.current_lose_streak += 1;
+            self.current_win_streak = 0;
+            self.best_lose_streak = self.best_lose_streak.max(self.current_lose_streak);
+        }
+    }
+}
+
+pub const PLAYER_STATS: Map<(&str, &Addr), PlayerStats> = Map::new("player_stats");
+
+pub const PLAYER_ACTIVITY: Map<&Addr, PlayerActivity> = Map::new("player_activity");
+
+pub const WAGER_WINDOWS: Map<(&str, &Addr), WagerWindow> = Map::new("wager_windows");
+
+// Denoms that bets can be placed in, with the terms for each, by asset key
+pub const DENOMS: Map<&str, DenomConfig> = Map::new("denoms");
+
+// House statistics per bet denom
+pub const STATS: Map<&str, HouseStats> = Map::new("stats");
+
+// Funds the house holds in one denom, as tracked by the contract itself. The
+// bankroll is owned by share holders, so its game result moves the share price.
+// Deposits and withdrawals are kept apart so the game result can be told apart.
+// Shares are priced on what pending games can't claim, the balance less what is
+// reserved for them, so nobody is paid for stakes the house may still hand back.
+// The entropy fees this bankroll paid are counted apart, since they may have been
+// paid for games in other denoms.
+#[cw_serde]
+#[derive(Default)]
+pub struct Bankroll {
+    pub balance: Uint128,
+    pub deposited: Uint128,
+    pub withdrawn: Uint128,
+    pub total_shares: Uint128,
+    pub entropy_fees: Uint128,
+}
+
+impl Bankroll {
+    // What the share holders own for sure, given what pending games have reserved
+    pub fn net(&self, reserved: Uint128) -> Uint128 {
+        self.balance.saturating_sub(reserved)
+    }
+
+    pub fn share_price(&self, reserved: Uint128) -> Decimal {
+        if self.total_shares.is_zero() {
+            return Decimal::one();
+        }
+        Decimal::checked_from_ratio(self.net(reserved), self.total_shares).unwrap_or(Decimal::MAX)
+    }
+
+    // What a number of shares is worth right now, rounded down
+    pub fn value_of(&self, shares: Uint128, reserved: Uint128) -> Uint128 {
+        if self.total_shares.is_zero() {
+            return Uint128::zero();
+        }
+        shares.multiply_ratio(self.net(reserved), self.total_shares)
+    }
+
+    // Add funds and return the shares they buy, rounded down. The first deposit buys
+    // shares one to one. A bankroll with nothing left to its share holders takes no
+    // deposits, the outstanding shares would otherwise take a cut of the new funds.
+    pub fn deposit(&mut self, amount: Uint128, reserved: Uint128) -> Result<Uint128, ContractError> {
+        let net = self.net(reserved);
+        let shares = if self.total_shares.is_zero() {
+            amount
+        } else if net.is_zero() {
+            return Err(ContractError::BankrollWipedOut {
+                shares: self.total_shares,
+            });
+        } else {
+            amount.multiply_ratio(self.total_shares, net)
+        };
+        self.balance = self.balance.checked_add(amount).map_err(StdError::from)?;
+        self.deposited = self.deposited.checked_add(amount).map_err(StdError::from)?;
+        self.total_shares = self.total_shares.checked_add(shares).map_err(StdError::from)?;
+        Ok(shares)
+    }
+
+    // Burn shares and return what they were worth
+    pub fn redeem(&mut self, shares: Uint128, reserved: Uint128) -> StdResult<Uint128> {
+        let amount = self.value_of(shares, reserved);
+        self.balance = self.balance.checked_sub(amount)?;
+        self.withdrawn = self.withdrawn.checked_add(amount)?;
+        self.total_shares = self.total_shares.checked_sub(shares)?;
+        Ok(amount)
+    }
+
+    // Money flowing in or out through play: bets, payouts, refunds and fees
+    pub fn receive(&mut self, amount: Uint128) -> StdResult<()> {
+        self.balance = self.balance.checked_add(amount)?;
+        Ok(())
+    }
+
+    pub fn pay(&mut self, amount: Uint128) -> StdResult<()> {
+        self.balance = self.balance.checked_sub(amount)?;
+        Ok(())
+    }
+
+    // The entropy beacon's fee for a game in any denom
+    pub fn pay_entropy_fee(&mut self, amount: Uint128) -> StdResult<()> {
+        self.pay(amount)?;
+        self.entropy_fees = self.entropy_fees.checked_add(amount)?;
+        Ok(())
+    }
+}
+
+pub const BANKROLL: Map<&str, Bankroll> = Map::new("bankroll");
+
+// Bankroll shares held by each liquidity provider, per denom
+pub const SHARES: Map<(&str, &Addr), Uint128> = Map::new("shares");
+
+// Shares on their way out, still carrying the game result until withdrawn
+#[cw_serde]
+pub struct Unbonding {
+    pub denom: AssetInfo,
+    pub shares: Uint128,
+    pub release_at: Expiration,
+}
+
+pub const UNBONDING: Map<&Addr, Vec<Unbonding>> = Map::new("unbonding");
+
+// Payouts reserved for games that are still pending, per bet denom
+pub const RESERVED: Map<&str, Uint128> = Map::new("reserved");
+
+// Fees accrued to each recipient, waiting to be claimed
+pub const FEES: Map<&Addr, Vec<AccruedFee>> = Map::new("fees");
+
+// When set, no new games can be started; pending games still settle or refund
+pub const PAUSED: Item<bool> = Item::new("paused");
+
+// An ownership transfer that still has to be accepted by the new owner
+#[cw_serde]
+pub struct PendingOwner {
+    pub new_owner: Addr,
+    pub expires: Expiration,
+}
+
+pub const PENDING_OWNER: Item<PendingOwner> = Item::new("pending_owner");
@@ -1,3 +1,2756 @@
+#[cfg(test)]
+#[allow(clippy::module_inception)]
+mod tests {
+    use crate::contract::{
+        calculate_payout, execute, get_outcome_from_entropy, get_segment_from_entropy, instantiate,
+        migrate, query,
+    };
+    use crate::msg::{
+        AccruedFeesResponse, BankrollResponse, ConfigResponse, DenomsResponse, ExecuteMsg,
+        GameResponse, GamesResponse, InstantiateMsg, MaxBetResponse, MigrateMsg, OddsResponse,
+        OrderBy, OwnershipResponse, PauseStatusResponse, PlayerStatsResponse, PositionResponse,
+        QueryMsg, ReceiveMsg, SegmentOdds, StatsResponse, UpdateConfigMsg, VaultResponse,
+    };
+    use crate::state::{
+        AccruedFee, AssetInfo, DenomConfig, FeeModel, FeeRecipient, GameStatus, HouseStats,
+        RateLimit, Segment, WagerCap, Wheel,
+    };
+    use crate::ContractError;
+    use cosmwasm_schema::cw_serde;
+    use cosmwasm_std::testing::{mock_dependencies, mock_env};
+    use cosmwasm_std::{
+        coin, coins, to_binary, Addr, Binary, Decimal, Deps, DepsMut, Empty, Env, MessageInfo,
+        Response, StdResult, Uint128, WasmMsg,
+    };
+    use cw20::{BalanceResponse, Cw20Coin, Cw20ExecuteMsg, Cw20QueryMsg, Cw20ReceiveMsg};
+    use cw_multi_test::{App, AppResponse, Contract, ContractWrapper, Executor};
+    use cw_storage_plus::Item;
+    use cw_utils::{Duration, Expiration, PaymentError};
+    use entropy_beacon_cosmos::beacon::{CalculateFeeResponse, RequestEntropyMsg};
+    use entropy_beacon_cosmos::{BeaconQueryMsg, EntropyCallbackMsg};
+    use sha2::{Digest, Sha512};
+    use std::str::FromStr;
+
+    const OWNER: &str = "owner";
+    const GUARDIAN: &str = "guardian";
+    const PLAYER1: &str = "player1";
+    const PLAYER2: &str = "player2";
+    const DENOM: &str = "ukuji";
+
+    // Stand-in for the entropy beacon: quotes the fee a test sets (zero unless set),
+    // queues every request and only calls back when a test fulfils a request explicitly.
+    #[cw_serde]
+    enum MockBeaconExecuteMsg {
+        RequestEntropy(RequestEntropyMsg),
+        Fulfil { request: u64, entropy: Vec<u8> },
+        SetFee { fee: u64 },
+    }
+
+    const REQUESTS: Item<Vec<(Addr, RequestEntropyMsg)>> = Item::new("requests");
+    const FEE: Item<u64> = Item::new("fee");
+
+    fn beacon_instantiate(
+        deps: DepsMut,
+        _env: Env,
+        _info: MessageInfo,
+        _msg: Empty,
+    ) -> StdResult<Response> {
+        REQUESTS.save(deps.storage, &vec![])?;
+        FEE.save(deps.storage, &0)?;
+        Ok(Response::new())
+    }
+
+    fn beacon_execute(
+        deps: DepsMut,
+        _env: Env,
+        info: MessageInfo,
+        msg: MockBeaconExecuteMsg,
+    ) -> StdResult<Response> {
+        let mut requests = REQUESTS.load(deps.storage)?;
+        match msg {
+            MockBeaconExecuteMsg::RequestEntropy(request) => {
+                requests.push((info.sender, request));
+                REQUESTS.save(deps.storage, &requests)?;
+                Ok(Response::new())
+            }
+            MockBeaconExecuteMsg::Fulfil { request, entropy } => {
+                let (requester, request) = requests[request as usize].clone();
+                let callback = ExecuteMsg::ReceiveEntropy(EntropyCallbackMsg {
+                    entropy,
+                    requester,
+                    msg: request.callback_msg,
+                });
+                Ok(Response::new().add_message(WasmMsg::Execute {
+                    contract_addr: request.callback_address.to_string(),
+                    msg: to_binary(&callback)?,
+                    funds: vec![],
+                }))
+            }
+            MockBeaconExecuteMsg::SetFee { fee } => {
+                FEE.save(deps.storage, &fee)?;
+                Ok(Response::new())
+            }
+        }
+    }
+
+    fn beacon_query(deps: Deps, _env: Env, _msg: BeaconQueryMsg) -> StdResult<Binary> {
+        to_binary(&CalculateFeeResponse {
+            fee: FEE.load(deps.storage)?,
+            gas_price: Decimal::zero(),
+        })
+    }
+
+    fn mock_beacon() -> Box<dyn Contract<Empty>> {
+        Box::new(ContractWrapper::new(
+            beacon_execute,
+            beacon_instantiate,
+            beacon_query,
+        ))
+    }
+
+    fn whale_of_fortune() -> Box<dyn Contract<Empty>> {
+        Box::new(ContractWrapper::new(execute, instantiate, query))
+    }
+
+    // Builds a wheel from (label, weight, payout) triples
+    fn wheel(segments: &[(&str, u32, &str)]) -> Wheel {
+        Wheel {
+            segments: segments
+                .iter()
+                .map(|&(label, weight, payout)| Segment {
+                    label: label.to_string(),
+                    weight,
+                    payout: Decimal::from_str(payout).unwrap(),
+                })
+                .collect(),
+        }
+    }
+
+    fn native(denom: &str) -> AssetInfo {
+        AssetInfo::Native(denom.into())
+    }
+
+    fn default_denom_config() -> DenomConfig {
+        DenomConfig {
+            denom: native(DENOM),
+            min_bet: Uint128::from(10u128),
+            max_bet: Uint128::from(10_000u128),
+            fee: FeeModel::Flat(Uint128::zero()),
+            wheel: None,
+            wager_cap: None,
+        }
+    }
+
+    fn default_instantiate_msg(beacon: &Addr) -> InstantiateMsg {
+        InstantiateMsg {
+            entropy_beacon_addr: beacon.to_string(),
+            owner_addr: OWNER.to_string(),
+            guardian_addr: Some(GUARDIAN.to_string()),
+            denoms: vec![default_denom_config()],
+            wheel: wheel(&[
+                ("1", 1, "1"),
+                ("3", 1, "3"),
+                ("5", 1, "5"),
+                ("6", 1, "6"),
+                ("6", 1, "6"),
+                ("joker", 1, "6"),
+                ("logo", 1, "6"),
+            ]),
+            min_house_edge: Decimal::percent(2),
+            max_exposure: Decimal::percent(10),
+            beacon_fee_denom: DENOM.into(),
+            fee_recipients: vec![FeeRecipient {
+                address: Addr::unchecked("fee_collector"),
+                weight: 1,
+            }],
+            rate_limit: RateLimit::default(),
+            entropy_timeout: Duration::Height(10),
+            callback_gas_limit: 100_000,
+            unbonding_period: Duration::Height(20),
+        }
+    }
+
+    // Spins up a beacon stand-in and a game contract holding a 100_000 bankroll
+    fn setup() -> (App, Addr, Addr) {
+        let mut app = App::new(|router, _, storage| {
+            for (addr, amount) in [(OWNER, 1_000_000u128), (PLAYER1, 1_000), (PLAYER2, 1_000)] {
+                router
+                    .bank
+                    .init_balance(storage, &Addr::unchecked(addr), coins(amount, DENOM))
+                    .unwrap();
+            }
+        });
+
+        let beacon_code = app.store_code(mock_beacon());
+        let beacon = app
+            .instantiate_contract(
+                beacon_code,
+                Addr::unchecked(OWNER),
+                &Empty {},
+                &[],
+                "beacon",
+                None,
+            )
+            .unwrap();
+
+        let game_code = app.store_code(whale_of_fortune());
+        let game = app
+            .instantiate_contract(
+                game_code,
+                Addr::unchecked(OWNER),
+                &default_instantiate_msg(&beacon),
+                &coins(100_000, DENOM),
+                "whale-of-fortune",
+                None,
+            )
+            .unwrap();
+
+        (app, game, beacon)
+    }
+
+    fn pull(
+        app: &mut App,
+        game: &Addr,
+        player: &str,
+        amount: u128,
+        bet_number: u128,
+    ) -> anyhow::Result<AppResponse> {
+        app.execute_contract(
+            Addr::unchecked(player),
+            game.clone(),
+            &ExecuteMsg::Pull {
+                bet_number: Uint128::from(bet_number),
+            },
+            &coins(amount, DENOM),
+        )
+    }
+
+    fn fulfil(
+        app: &mut App,
+        beacon: &Addr,
+        request: u64,
+        entropy: Vec<u8>,
+    ) -> anyhow::Result<AppResponse> {
+        app.execute_contract(
+            Addr::unchecked(OWNER),
+            beacon.clone(),
+            &MockBeaconExecuteMsg::Fulfil { request, entropy },
+            &[],
+        )
+    }
+
+    // Finds some entropy that makes the wheel land on the given number
+    fn entropy_for(outcome: u8) -> Vec<u8> {
+        (0..=u8::MAX)
+            .map(|seed| vec![seed; 64])
+            .find(|entropy| get_outcome_from_entropy(entropy, 7) == outcome as u64)
+            .unwrap()
+    }
+
+    fn query_game(app: &App, game: &Addr, idx: u128) -> GameResponse {
+        app.wrap()
+            .query_wasm_smart(
+                game,
+                &QueryMsg::Game {
+                    idx: Uint128::from(idx),
+                },
+            )
+            .unwrap()
+    }
+
+    fn attribute(res: &AppResponse, key: &str) -> Option<String> {
+        res.events
+            .iter()
+            .flat_map(|event| event.attributes.iter())
+            .find(|attr| attr.key == key)
+            .map(|attr| attr.value.clone())
+    }
+
+    #[test]
+    fn interleaved_pulls_get_their_own_games() {
+        let (mut app, game, _beacon) = setup();
+
+        let res = pull(&mut app, &game, PLAYER1, 100, 0).unwrap();
+        assert_eq!(attribute(&res, "game"), Some("0".to_string()));
+        let res = pull(&mut app, &game, PLAYER2, 200, 3).unwrap();
+        assert_eq!(attribute(&res, "game"), Some("1".to_string()));
+
+        assert_eq!(query_game(&app, &game, 0).player, Addr::unchecked(PLAYER1));
+        assert_eq!(query_game(&app, &game, 1).player, Addr::unchecked(PLAYER2));
+    }
+
+    #[test]
+    fn out_of_order_callbacks_resolve_the_right_games() {
+        let (mut app, game, beacon) = setup();
+
+        pull(&mut app, &game, PLAYER1, 100, 0).unwrap();
+        pull(&mut app, &game, PLAYER2, 100, 0).unwrap();
+
+        // The second request is answered before the first one
+        fulfil(&mut app, &beacon, 1, entropy_for(4)).unwrap();
+        assert_eq!(query_game(&app, &game, 0).outcome, None);
+        assert_eq!(
+            query_game(&app, &game, 0).status,
+            GameStatus::AwaitingEntropy
+        );
+        assert_eq!(query_game(&app, &game, 1).outcome, Some(4));
+
+        fulfil(&mut app, &beacon, 0, entropy_for(2)).unwrap();
+        assert_eq!(query_game(&app, &game, 0).outcome, Some(2));
+        assert_eq!(query_game(&app, &game, 1).outcome, Some(4));
+    }
+
+    #[test]
+    fn entropy_is_applied_once() {
+        let (mut app, game, beacon) = setup();
+
+        pull(&mut app, &game, PLAYER1, 100, 0).unwrap();
+        fulfil(&mut app, &beacon, 0, entropy_for(1)).unwrap();
+
+        let err = fulfil(&mut app, &beacon, 0, entropy_for(5)).unwrap_err();
+        assert_eq!(
+            err.downcast::<ContractError>().unwrap(),
+            ContractError::InvalidGameTransition {
+                game_id: Uint128::zero(),
+                from: GameStatus::Resolved,
+                to: GameStatus::Resolved,
+            }
+        );
+        assert_eq!(query_game(&app, &game, 0).outcome, Some(1));
+    }
+
+    fn balance(app: &App, addr: &str) -> u128 {
+        app.wrap().query_balance(addr, DENOM).unwrap().amount.u128()
+    }
+
+    #[test]
+    fn winning_pull_is_paid_out_in_the_callback() {
+        let (mut app, game, beacon) = setup();
+
+        // Player1 bets 100 on number 1, which pays 3:1
+        pull(&mut app, &game, PLAYER1, 100, 1).unwrap();
+        assert_eq!(balance(&app, PLAYER1), 900);
+
+        let res = fulfil(&mut app, &beacon, 0, entropy_for(1)).unwrap();
+        assert_eq!(attribute(&res, "result"), Some("win".to_string()));
+        assert_eq!(attribute(&res, "payout"), Some("300".to_string()));
+        assert_eq!(balance(&app, PLAYER1), 1_200);
+        let settled = query_game(&app, &game, 0);
+        assert_eq!(settled.status, GameStatus::PaidOut);
+        assert_eq!(settled.payout, Uint128::from(300u128));
+    }
+
+    #[test]
+    fn losing_pull_keeps_the_bet() {
+        let (mut app, game, beacon) = setup();
+
+        pull(&mut app, &game, PLAYER1, 100, 1).unwrap();
+        let res = fulfil(&mut app, &beacon, 0, entropy_for(2)).unwrap();
+        assert_eq!(attribute(&res, "result"), Some("lose".to_string()));
+        assert_eq!(balance(&app, PLAYER1), 900);
+        assert_eq!(balance(&app, game.as_str()), 100_100);
+        assert_eq!(query_game(&app, &game, 0).status, GameStatus::Resolved);
+    }
+
+    #[test]
+    fn bet_number_cannot_be_changed_after_the_pull() {
+        let (mut app, game, beacon) = setup();
+
+        // Interleaved pulls on different numbers, both resolved to number 3
+        pull(&mut app, &game, PLAYER1, 100, 3).unwrap();
+        pull(&mut app, &game, PLAYER2, 100, 0).unwrap();
+        fulfil(&mut app, &beacon, 1, entropy_for(3)).unwrap();
+        fulfil(&mut app, &beacon, 0, entropy_for(3)).unwrap();
+
+        // Only the player who picked 3 before the outcome was known wins
+        assert_eq!(balance(&app, PLAYER1), 1_500);
+        assert_eq!(balance(&app, PLAYER2), 900);
+    }
+
+    fn refund(
+        app: &mut App,
+        game: &Addr,
+        sender: &str,
+        game_id: u128,
+    ) -> anyhow::Result<AppResponse> {
+        app.execute_contract(
+            Addr::unchecked(sender),
+            game.clone(),
+            &ExecuteMsg::Refund {
+                game_id: Uint128::from(game_id),
+            },
+            &[],
+        )
+    }
+
+    fn advance_blocks(app: &mut App, blocks: u64) {
+        app.update_block(|block| {
+            block.height += blocks;
+            block.time = block.time.plus_seconds(5 * blocks);
+        });
+    }
+
+    #[test]
+    fn refund_is_rejected_before_the_timeout() {
+        let (mut app, game, _beacon) = setup();
+
+        pull(&mut app, &game, PLAYER1, 100, 0).unwrap();
+        advance_blocks(&mut app, 9);
+
+        let err = refund(&mut app, &game, PLAYER1, 0).unwrap_err();
+        assert_eq!(
+            err.downcast::<ContractError>().unwrap(),
+            ContractError::GameNotExpired {
+                game_id: Uint128::zero(),
+                expires: query_game(&app, &game, 0).expires,
+            }
+        );
+    }
+
+    #[test]
+    fn unanswered_game_is_refunded_after_the_timeout() {
+        let (mut app, game, _beacon) = setup();
+
+        // The beacon stand-in never answers this request
+        pull(&mut app, &game, PLAYER1, 100, 0).unwrap();
+        assert_eq!(balance(&app, PLAYER1), 900);
+        advance_blocks(&mut app, 10);
+
+        // Anyone can trigger the refund, the stake goes back to the player
+        let res = refund(&mut app, &game, PLAYER2, 0).unwrap();
+        assert_eq!(attribute(&res, "refund"), Some("100".to_string()));
+        assert_eq!(balance(&app, PLAYER1), 1_000);
+        assert_eq!(balance(&app, PLAYER2), 1_000);
+        assert_eq!(query_game(&app, &game, 0).status, GameStatus::Refunded);
+
+        // A refunded game cannot be refunded again
+        let err = refund(&mut app, &game, PLAYER1, 0).unwrap_err();
+        assert_eq!(
+            err.downcast::<ContractError>().unwrap(),
+            ContractError::InvalidGameTransition {
+                game_id: Uint128::zero(),
+                from: GameStatus::Refunded,
+                to: GameStatus::Refunded,
+            }
+        );
+    }
+
+    #[test]
+    fn late_entropy_expires_the_game_instead_of_settling_it() {
+        let (mut app, game, beacon) = setup();
+
+        pull(&mut app, &game, PLAYER1, 100, 3).unwrap();
+        advance_blocks(&mut app, 10);
+
+        // Even a winning outcome is ignored once the timeout has passed
+        let res = fulfil(&mut app, &beacon, 0, entropy_for(3)).unwrap();
+        assert_eq!(attribute(&res, "result"), Some("expired".to_string()));
+        assert_eq!(balance(&app, PLAYER1), 900);
+        assert_eq!(query_game(&app, &game, 0).status, GameStatus::Expired);
+
+        refund(&mut app, &game, PLAYER1, 0).unwrap();
+        assert_eq!(balance(&app, PLAYER1), 1_000);
+        assert_eq!(query_game(&app, &game, 0).status, GameStatus::Refunded);
+    }
+
+    #[test]
+    fn entropy_for_a_refunded_game_is_acknowledged() {
+        let (mut app, game, beacon) = setup();
+
+        pull(&mut app, &game, PLAYER1, 100, 3).unwrap();
+        pull(&mut app, &game, PLAYER2, 100, 3).unwrap();
+        advance_blocks(&mut app, 10);
+        refund(&mut app, &game, PLAYER1, 0).unwrap();
+
+        // The callback succeeds without touching the refunded game
+        let res = fulfil(&mut app, &beacon, 0, entropy_for(3)).unwrap();
+        assert_eq!(attribute(&res, "result"), Some("refunded".to_string()));
+        assert_eq!(balance(&app, PLAYER1), 1_000);
+        assert_eq!(query_game(&app, &game, 0).status, GameStatus::Refunded);
+
+        // Nor does a second callback for a game that already expired
+        fulfil(&mut app, &beacon, 1, entropy_for(3)).unwrap();
+        let res = fulfil(&mut app, &beacon, 1, entropy_for(3)).unwrap();
+        assert_eq!(attribute(&res, "result"), Some("expired".to_string()));
+        assert_eq!(query_game(&app, &game, 1).status, GameStatus::Expired);
+    }
+
+    #[test]
+    fn settled_game_cannot_be_refunded() {
+        let (mut app, game, beacon) = setup();
+
+        pull(&mut app, &game, PLAYER1, 100, 0).unwrap();
+        fulfil(&mut app, &beacon, 0, entropy_for(1)).unwrap();
+        advance_blocks(&mut app, 10);
+
+        let err = refund(&mut app, &game, PLAYER1, 0).unwrap_err();
+        assert_eq!(
+            err.downcast::<ContractError>().unwrap(),
+            ContractError::InvalidGameTransition {
+                game_id: Uint128::zero(),
+                from: GameStatus::Resolved,
+                to: GameStatus::Refunded,
+            }
+        );
+    }
+
+    // Draws outcomes for many synthetic entropy inputs and checks them with a
+    // chi-squared goodness-of-fit test against the uniform distribution
+    fn assert_uniform_outcomes(wheel_size: u64, samples: u64) {
+        let mut counts = vec![0u64; wheel_size as usize];
+        for i in 0..samples {
+            let entropy = Sha512::digest(i.to_be_bytes());
+            let outcome = get_outcome_from_entropy(&entropy, wheel_size);
+            assert!(outcome < wheel_size);
+            counts[outcome as usize] += 1;
+        }
+
+        let expected = samples as f64 / wheel_size as f64;
+        let chi_squared: f64 = counts
+            .iter()
+            .map(|&count| (count as f64 - expected).powi(2) / expected)
+            .sum();
+
+        // Critical value at p = 0.001 (Wilson-Hilferty approximation)
+        let df = (wheel_size - 1) as f64;
+        let critical = df * (1.0 - 2.0 / (9.0 * df) + 3.09 * (2.0 / (9.0 * df)).sqrt()).powi(3);
+        assert!(
+        chi_squared < critical,
+        "wheel of {wheel_size}: chi-squared {chi_squared} exceeds {critical}, counts {counts:?}"
+    );
+    }
+
+    #[test]
+    fn outcomes_are_uniform() {
+        assert_uniform_outcomes(7, 70_000);
+        assert_uniform_outcomes(2, 20_000);
+        assert_uniform_outcomes(54, 108_000);
+    }
+
+    #[test]
+    fn outcomes_use_all_of_the_entropy() {
+        // Entropy that only differs in its first byte must still be able to land elsewhere
+        let outcomes: Vec<u64> = (0..=u8::MAX)
+            .map(|first| {
+                let mut entropy = vec![0u8; 64];
+                entropy[0] = first;
+                get_outcome_from_entropy(&entropy, 7)
+            })
+            .collect();
+        assert!(outcomes.iter().any(|&outcome| outcome != outcomes[0]));
+    }
+
+    #[test]
+    fn segments_are_hit_in_proportion_to_their_weight() {
+        let wheel = wheel(&[
+            ("1", 23, "2"),
+            ("2", 15, "3"),
+            ("5", 8, "6"),
+            ("10", 4, "11"),
+            ("joker", 1, "41"),
+        ]);
+        let total_weight = wheel.total_weight();
+        let samples = 100_000u64;
+
+        let mut counts = vec![0u64; wheel.segments.len()];
+        for i in 0..samples {
+            let entropy = Sha512::digest(i.to_be_bytes());
+            counts[get_segment_from_entropy(&entropy, &wheel) as usize] += 1;
+        }
+
+        let chi_squared: f64 = counts
+            .iter()
+            .zip(&wheel.segments)
+            .map(|(&count, segment)| {
+                let expected = samples as f64 * segment.weight as f64 / total_weight as f64;
+                (count as f64 - expected).powi(2) / expected
+            })
+            .sum();
+
+        // Critical value for 4 degrees of freedom at p = 0.001
+        assert!(
+            chi_squared < 18.47,
+            "chi-squared {chi_squared}, counts {counts:?}"
+        );
+    }
+
+    #[test]
+    fn slots_map_onto_segments_in_order() {
+        let wheel = wheel(&[("a", 2, "1"), ("b", 1, "1"), ("c", 3, "1")]);
+        let segments: Vec<u8> = (0..6).map(|slot| wheel.segment_at(slot)).collect();
+        assert_eq!(segments, vec![0, 0, 1, 2, 2, 2]);
+    }
+
+    fn update_config(
+        app: &mut App,
+        game: &Addr,
+        sender: &str,
+        update: UpdateConfigMsg,
+    ) -> anyhow::Result<AppResponse> {
+        app.execute_contract(
+            Addr::unchecked(sender),
+            game.clone(),
+            &ExecuteMsg::UpdateConfig(update),
+            &[],
+        )
+    }
+
+    #[test]
+    fn update_config_is_owner_only() {
+        let (mut app, game, _beacon) = setup();
+
+        let err = update_config(
+            &mut app,
+            &game,
+            PLAYER1,
+            UpdateConfigMsg {
+                max_exposure: Some(Decimal::percent(1)),
+                ..UpdateConfigMsg::default()
+            },
+        )
+        .unwrap_err();
+        assert_eq!(
+            err.downcast::<ContractError>().unwrap(),
+            ContractError::Unauthorized {}
+        );
+
+        // The old exposure limit still applies
+        pull(&mut app, &game, PLAYER1, 500, 0).unwrap();
+    }
+
+    #[test]
+    fn owner_updates_only_the_given_fields() {
+        let (mut app, game, _beacon) = setup();
+
+        let res = update_config(
+            &mut app,
+            &game,
+            OWNER,
+            UpdateConfigMsg {
+                max_exposure: Some(Decimal::percent(1)),
+                callback_gas_limit: Some(150_000),
+                ..UpdateConfigMsg::default()
+            },
+        )
+        .unwrap();
+        assert_eq!(attribute(&res, "max_exposure"), Some("0.01".to_string()));
+        assert_eq!(
+            attribute(&res, "callback_gas_limit"),
+            Some("150000".to_string())
+        );
+        assert_eq!(attribute(&res, "min_house_edge"), None);
+
+        // 1% of the 100_000 bankroll over a 6x payout caps bets at 166,
+        // the minimum bet of the denom still applies too
+        pull(&mut app, &game, PLAYER1, 167, 0).unwrap_err();
+        pull(&mut app, &game, PLAYER1, 5, 0).unwrap_err();
+        pull(&mut app, &game, PLAYER1, 166, 0).unwrap();
+    }
+
+    #[test]
+    fn update_config_rejects_inconsistent_values() {
+        let (mut app, game, _beacon) = setup();
+
+        let err = add_denom(
+            &mut app,
+            &game,
+            OWNER,
+            DenomConfig {
+                min_bet: Uint128::from(20_000u128),
+                ..default_denom_config()
+            },
+        )
+        .unwrap_err();
+        assert_eq!(
+            err.downcast::<ContractError>().unwrap(),
+            ContractError::InvalidBetLimits {
+                min_bet: Uint128::from(20_000u128),
+                max_bet: Uint128::from(10_000u128),
+            }
+        );
+
+        let err = update_config(
+            &mut app,
+            &game,
+            OWNER,
+            UpdateConfigMsg {
+                callback_gas_limit: Some(0),
+                ..UpdateConfigMsg::default()
+            },
+        )
+        .unwrap_err();
+        assert_eq!(
+            err.downcast::<ContractError>().unwrap(),
+            ContractError::InvalidCallbackGasLimit {}
+        );
+    }
+
+    fn add_denom(
+        app: &mut App,
+        game: &Addr,
+        sender: &str,
+        denom_config: DenomConfig,
+    ) -> anyhow::Result<AppResponse> {
+        execute_as(app, game, sender, &ExecuteMsg::AddDenom(denom_config))
+    }
+
+    const NEW_OWNER: &str = "new_owner";
+
+    fn execute_as(
+        app: &mut App,
+        game: &Addr,
+        sender: &str,
+        msg: &ExecuteMsg,
+    ) -> anyhow::Result<AppResponse> {
+        app.execute_contract(Addr::unchecked(sender), game.clone(), msg, &[])
+    }
+
+    fn query_ownership(app: &App, game: &Addr) -> OwnershipResponse {
+        app.wrap()
+            .query_wasm_smart(game, &QueryMsg::Ownership {})
+            .unwrap()
+    }
+
+    #[test]
+    fn ownership_is_transferred_in_two_steps() {
+        let (mut app, game, _beacon) = setup();
+
+        let propose = ExecuteMsg::ProposeOwner {
+            new_owner: NEW_OWNER.to_string(),
+            expiry: None,
+        };
+        let err = execute_as(&mut app, &game, PLAYER1, &propose).unwrap_err();
+        assert_eq!(
+            err.downcast::<ContractError>().unwrap(),
+            ContractError::Unauthorized {}
+        );
+        execute_as(&mut app, &game, OWNER, &propose).unwrap();
+
+        // Until accepted, the old owner stays in charge
+        let ownership = query_ownership(&app, &game);
+        assert_eq!(ownership.owner, Some(Addr::unchecked(OWNER)));
+        assert_eq!(ownership.pending_owner, Some(Addr::unchecked(NEW_OWNER)));
+        assert_eq!(ownership.pending_expiry, Some(Expiration::Never {}));
+
+        // Nobody but the proposed owner can accept
+        let err =
+            execute_as(&mut app, &game, PLAYER1, &ExecuteMsg::AcceptOwnership {}).unwrap_err();
+        assert_eq!(
+            err.downcast::<ContractError>().unwrap(),
+            ContractError::Unauthorized {}
+        );
+        execute_as(&mut app, &game, NEW_OWNER, &ExecuteMsg::AcceptOwnership {}).unwrap();
+
+        let ownership = query_ownership(&app, &game);
+        assert_eq!(ownership.owner, Some(Addr::unchecked(NEW_OWNER)));
+        assert_eq!(ownership.pending_owner, None);
+
+        // The old owner lost its rights, the new one has them
+        let update = UpdateConfigMsg {
+            max_exposure: Some(Decimal::percent(5)),
+            ..UpdateConfigMsg::default()
+        };
+        update_config(&mut app, &game, OWNER, update.clone()).unwrap_err();
+        update_config(&mut app, &game, NEW_OWNER, update).unwrap();
+    }
+
+    #[test]
+    fn expired_or_cancelled_transfer_cannot_be_accepted() {
+        let (mut app, game, _beacon) = setup();
+        let expiry = Expiration::AtHeight(app.block_info().height + 5);
+
+        let propose = ExecuteMsg::ProposeOwner {
+            new_owner: NEW_OWNER.to_string(),
+            expiry: Some(expiry),
+        };
+        execute_as(&mut app, &game, OWNER, &propose).unwrap();
+        advance_blocks(&mut app, 5);
+        let err =
+            execute_as(&mut app, &game, NEW_OWNER, &ExecuteMsg::AcceptOwnership {}).unwrap_err();
+        assert_eq!(
+            err.downcast::<ContractError>().unwrap(),
+            ContractError::OwnershipTransferExpired {}
+        );
+
+        let propose = ExecuteMsg::ProposeOwner {
+            new_owner: NEW_OWNER.to_string(),
+            expiry: None,
+        };
+        execute_as(&mut app, &game, OWNER, &propose).unwrap();
+        execute_as(
+            &mut app,
+            &game,
+            OWNER,
+            &ExecuteMsg::CancelOwnershipTransfer {},
+        )
+        .unwrap();
+        let err =
+            execute_as(&mut app, &game, NEW_OWNER, &ExecuteMsg::AcceptOwnership {}).unwrap_err();
+        assert_eq!(
+            err.downcast::<ContractError>().unwrap(),
+            ContractError::NoPendingOwner {}
+        );
+        assert_eq!(
+            query_ownership(&app, &game).owner,
+            Some(Addr::unchecked(OWNER))
+        );
+    }
+
+    #[test]
+    fn renounced_ownership_locks_owner_actions() {
+        let (mut app, game, _beacon) = setup();
+
+        execute_as(&mut app, &game, OWNER, &ExecuteMsg::RenounceOwnership {}).unwrap();
+        assert_eq!(query_ownership(&app, &game).owner, None);
+
+        let err = update_config(
+            &mut app,
+            &game,
+            OWNER,
+            UpdateConfigMsg {
+                max_exposure: Some(Decimal::percent(5)),
+                ..UpdateConfigMsg::default()
+            },
+        )
+        .unwrap_err();
+        assert_eq!(
+            err.downcast::<ContractError>().unwrap(),
+            ContractError::Unauthorized {}
+        );
+
+        // Games keep working without an owner
+        pull(&mut app, &game, PLAYER1, 100, 0).unwrap();
+    }
+
+    fn query_pause_status(app: &App, game: &Addr) -> PauseStatusResponse {
+        app.wrap()
+            .query_wasm_smart(game, &QueryMsg::PauseStatus {})
+            .unwrap()
+    }
+
+    #[test]
+    fn guardian_pauses_new_pulls_but_pending_games_finish() {
+        let (mut app, game, beacon) = setup();
+
+        pull(&mut app, &game, PLAYER1, 100, 1).unwrap();
+        pull(&mut app, &game, PLAYER2, 100, 0).unwrap();
+
+        let err = execute_as(&mut app, &game, PLAYER1, &ExecuteMsg::Pause {}).unwrap_err();
+        assert_eq!(
+            err.downcast::<ContractError>().unwrap(),
+            ContractError::Unauthorized {}
+        );
+        execute_as(&mut app, &game, GUARDIAN, &ExecuteMsg::Pause {}).unwrap();
+        assert_eq!(
+            query_pause_status(&app, &game),
+            PauseStatusResponse {
+                paused: true,
+                guardian: Some(Addr::unchecked(GUARDIAN)),
+            }
+        );
+
+        let err = pull(&mut app, &game, PLAYER1, 100, 0).unwrap_err();
+        assert_eq!(
+            err.downcast::<ContractError>().unwrap(),
+            ContractError::Paused {}
+        );
+
+        // Pending games still settle and refund while paused
+        fulfil(&mut app, &beacon, 0, entropy_for(1)).unwrap();
+        assert_eq!(balance(&app, PLAYER1), 1_200);
+        advance_blocks(&mut app, 10);
+        refund(&mut app, &game, PLAYER2, 1).unwrap();
+        assert_eq!(balance(&app, PLAYER2), 1_000);
+    }
+
+    #[test]
+    fn only_the_owner_unpauses() {
+        let (mut app, game, _beacon) = setup();
+
+        execute_as(&mut app, &game, OWNER, &ExecuteMsg::Pause {}).unwrap();
+        let err = execute_as(&mut app, &game, GUARDIAN, &ExecuteMsg::Unpause {}).unwrap_err();
+        assert_eq!(
+            err.downcast::<ContractError>().unwrap(),
+            ContractError::Unauthorized {}
+        );
+
+        execute_as(&mut app, &game, OWNER, &ExecuteMsg::Unpause {}).unwrap();
+        assert!(!query_pause_status(&app, &game).paused);
+        pull(&mut app, &game, PLAYER1, 100, 0).unwrap();
+
+        // A removed guardian cannot pause anymore
+        let set_guardian = ExecuteMsg::SetGuardian {
+            guardian_addr: None,
+        };
+        execute_as(&mut app, &game, OWNER, &set_guardian).unwrap();
+        execute_as(&mut app, &game, GUARDIAN, &ExecuteMsg::Pause {}).unwrap_err();
+    }
+
+    #[test]
+    fn config_query_reports_the_current_config() {
+        let (mut app, game, beacon) = setup();
+
+        update_config(
+            &mut app,
+            &game,
+            OWNER,
+            UpdateConfigMsg {
+                callback_gas_limit: Some(150_000),
+                ..UpdateConfigMsg::default()
+            },
+        )
+        .unwrap();
+
+        let config: ConfigResponse = app
+            .wrap()
+            .query_wasm_smart(&game, &QueryMsg::Config {})
+            .unwrap();
+        let msg = default_instantiate_msg(&beacon);
+        assert_eq!(
+            config,
+            ConfigResponse {
+                entropy_beacon_addr: beacon,
+                owner: Some(Addr::unchecked(OWNER)),
+                guardian: Some(Addr::unchecked(GUARDIAN)),
+                wheel: msg.wheel,
+                min_house_edge: msg.min_house_edge,
+                max_exposure: msg.max_exposure,
+                beacon_fee_denom: msg.beacon_fee_denom,
+                fee_recipients: msg.fee_recipients,
+                rate_limit: msg.rate_limit,
+                entropy_timeout: msg.entropy_timeout,
+                callback_gas_limit: 150_000,
+                unbonding_period: msg.unbonding_period,
+            }
+        );
+    }
+
+    fn games_by_player(
+        app: &App,
+        game: &Addr,
+        player: &str,
+        start_after: Option<u128>,
+        limit: Option<u32>,
+        order: Option<OrderBy>,
+    ) -> Vec<u128> {
+        let res: GamesResponse = app
+            .wrap()
+            .query_wasm_smart(
+                game,
+                &QueryMsg::GamesByPlayer {
+                    player: player.to_string(),
+                    start_after: start_after.map(Uint128::from),
+                    limit,
+                    order,
+                },
+            )
+            .unwrap();
+        res.games.iter().map(|game| game.idx.u128()).collect()
+    }
+
+    #[test]
+    fn games_are_listed_per_player_with_pagination() {
+        let (mut app, game, _beacon) = setup();
+
+        // Player1 plays games 0, 2, 3 and 5, player2 plays 1 and 4
+        for player in [PLAYER1, PLAYER2, PLAYER1, PLAYER1, PLAYER2, PLAYER1] {
+            pull(&mut app, &game, player, 10, 0).unwrap();
+        }
+
+        // Newest first by default
+        assert_eq!(
+            games_by_player(&app, &game, PLAYER1, None, None, None),
+            vec![5, 3, 2, 0]
+        );
+        assert_eq!(
+            games_by_player(&app, &game, PLAYER1, Some(3), Some(1), None),
+            vec![2]
+        );
+        assert_eq!(
+            games_by_player(
+                &app,
+                &game,
+                PLAYER1,
+                Some(0),
+                None,
+                Some(OrderBy::Ascending)
+            ),
+            vec![2, 3, 5]
+        );
+        assert_eq!(
+            games_by_player(&app, &game, PLAYER2, None, None, Some(OrderBy::Ascending)),
+            vec![1, 4]
+        );
+        assert!(games_by_player(&app, &game, OWNER, None, None, None).is_empty());
+    }
+
+    #[test]
+    fn all_games_are_listed_with_pagination_limits() {
+        let (mut app, game, _beacon) = setup();
+
+        for _ in 0..35 {
+            pull(&mut app, &game, PLAYER1, 10, 0).unwrap();
+        }
+
+        let list = |start_after: Option<u128>, limit: Option<u32>| -> Vec<u128> {
+            let res: GamesResponse = app
+                .wrap()
+                .query_wasm_smart(
+                    &game,
+                    &QueryMsg::Games {
+                        start_after: start_after.map(Uint128::from),
+                        limit,
+                    },
+                )
+                .unwrap();
+            res.games.iter().map(|game| game.idx.u128()).collect()
+        };
+
+        assert_eq!(list(None, None), (0..10).collect::<Vec<_>>());
+        assert_eq!(list(Some(9), Some(3)), vec![10, 11, 12]);
+        // Limits are capped
+        assert_eq!(list(None, Some(100)).len(), 30);
+        assert_eq!(list(Some(30), None), vec![31, 32, 33, 34]);
+    }
+
+    #[test]
+    fn house_stats_track_settlements_and_refunds() {
+        let (mut app, game, beacon) = setup();
+
+        // A 3:1 win, a loss and a refund
+        pull(&mut app, &game, PLAYER1, 100, 1).unwrap();
+        pull(&mut app, &game, PLAYER2, 200, 0).unwrap();
+        pull(&mut app, &game, PLAYER2, 50, 6).unwrap();
+        fulfil(&mut app, &beacon, 0, entropy_for(1)).unwrap();
+        fulfil(&mut app, &beacon, 1, entropy_for(1)).unwrap();
+        advance_blocks(&mut app, 10);
+        refund(&mut app, &game, PLAYER2, 2).unwrap();
+
+        let res: StatsResponse = app
+            .wrap()
+            .query_wasm_smart(&game, &QueryMsg::Stats {})
+            .unwrap();
+        assert_eq!(res.stats.len(), 1);
+        let stats = &res.stats[0];
+        assert_eq!(stats.denom, DENOM);
+        assert_eq!(
+            stats.stats,
+            HouseStats {
+                games_settled: 2,
+                games_won: 1,
+                games_refunded: 1,
+                total_wagered: Uint128::from(300u128),
+                total_paid_out: Uint128::from(300u128),
+                total_refunded: Uint128::from(50u128),
+                fees_collected: Uint128::zero(),
+                bets_per_number: vec![1, 1],
+                wins_per_number: vec![0, 1],
+                hits_per_number: vec![0, 2],
+            }
+        );
+        assert_eq!(stats.realised_rtp, Decimal::one());
+        assert_eq!(stats.theoretical_rtp[1], Decimal::from_ratio(3u128, 7u128));
+    }
+
+    fn query_player_stats(app: &App, game: &Addr, address: &str) -> PlayerStatsResponse {
+        app.wrap()
+            .query_wasm_smart(
+                game,
+                &QueryMsg::PlayerStats {
+                    address: address.to_string(),
+                    denom: native(DENOM),
+                },
+            )
+            .unwrap()
+    }
+
+    #[test]
+    fn player_stats_track_results_and_streaks() {
+        let (mut app, game, beacon) = setup();
+
+        // Player1 wins, wins, loses, loses, loses, wins on number 1 (3:1)
+        for (request, outcome) in [1, 1, 0, 0, 0, 1].into_iter().enumerate() {
+            pull(&mut app, &game, PLAYER1, 100, 1).unwrap();
+            fulfil(&mut app, &beacon, request as u64, entropy_for(outcome)).unwrap();
+        }
+
+        let stats = query_player_stats(&app, &game, PLAYER1);
+        assert_eq!(
+            stats,
+            PlayerStatsResponse {
+                address: Addr::unchecked(PLAYER1),
+                denom: native(DENOM),
+                games_played: 6,
+                total_wagered: Uint128::from(600u128),
+                total_won: Uint128::from(900u128),
+                net_gain: Uint128::from(300u128),
+                net_loss: Uint128::zero(),
+                biggest_win: Uint128::from(300u128),
+                current_win_streak: 1,
+                current_lose_streak: 0,
+                best_win_streak: 2,
+                best_lose_streak: 3,
+            }
+        );
+
+        // Other players keep their own record
+        let stats = query_player_stats(&app, &game, PLAYER2);
+        assert_eq!(stats.games_played, 0);
+        assert_eq!(stats.net_loss, Uint128::zero());
+    }
+
+    #[test]
+    fn wheel_is_validated() {
+        let (mut app, game, beacon) = setup();
+
+        for (segments, reason) in [
+            (vec![], "must have between 1 and 256 segments"),
+            (vec![("a", 1, "2"), ("", 1, "2")], "segment 1 has no label"),
+            (
+                vec![("a", 0, "2")],
+                "segment 0 must take up at least one slot",
+            ),
+            (
+                vec![("a", 1, "2"), ("b", 1, "0")],
+                "segment 1 must pay out something",
+            ),
+        ] {
+            let err = update_config(
+                &mut app,
+                &game,
+                OWNER,
+                UpdateConfigMsg {
+                    wheel: Some(wheel(&segments)),
+                    ..Default::default()
+                },
+            )
+            .unwrap_err();
+            assert_eq!(
+                err.downcast::<ContractError>().unwrap(),
+                ContractError::InvalidWheel {
+                    reason: reason.to_string()
+                }
+            );
+        }
+
+        // A bet can only be placed on a segment of the new wheel
+        update_config(
+            &mut app,
+            &game,
+            OWNER,
+            UpdateConfigMsg {
+                wheel: Some(wheel(&[("even", 1, "1.9"), ("odd", 1, "1.9")])),
+                ..Default::default()
+            },
+        )
+        .unwrap();
+        let err = pull(&mut app, &game, PLAYER1, 100, 2).unwrap_err();
+        assert_eq!(
+            err.downcast::<ContractError>().unwrap(),
+            ContractError::InvalidBetNumber {}
+        );
+
+        // The settlement reports the label of the segment the wheel landed on
+        pull(&mut app, &game, PLAYER1, 100, 1).unwrap();
+        let entropy = (0..=u8::MAX)
+            .map(|seed| vec![seed; 64])
+            .find(|entropy| get_outcome_from_entropy(entropy, 2) == 1)
+            .unwrap();
+        let res = fulfil(&mut app, &beacon, 0, entropy).unwrap();
+        assert_eq!(attribute(&res, "segment"), Some("odd".to_string()));
+        assert_eq!(query_game(&app, &game, 0).payout, Uint128::from(190u128));
+    }
+
+    #[test]
+    fn fractional_payouts_round_down() {
+        let (mut app, game, beacon) = setup();
+        update_config(
+            &mut app,
+            &game,
+            OWNER,
+            UpdateConfigMsg {
+                wheel: Some(wheel(&[("consolation", 1, "0.5"), ("double", 1, "1.9")])),
+                ..Default::default()
+            },
+        )
+        .unwrap();
+        let entropy_for_segment = |outcome: u64| {
+            (0..=u8::MAX)
+                .map(|seed| vec![seed; 64])
+                .find(|entropy| get_outcome_from_entropy(entropy, 2) == outcome)
+                .unwrap()
+        };
+
+        // 15 * 1.9 = 28.5 pays 28, stake included
+        pull(&mut app, &game, PLAYER1, 15, 1).unwrap();
+        fulfil(&mut app, &beacon, 0, entropy_for_segment(1)).unwrap();
+        assert_eq!(query_game(&app, &game, 0).payout, Uint128::from(28u128));
+        assert_eq!(balance(&app, PLAYER1), 1_013);
+
+        // 11 * 0.5 = 5.5 hands back 5 of the stake
+        pull(&mut app, &game, PLAYER1, 11, 0).unwrap();
+        fulfil(&mut app, &beacon, 1, entropy_for_segment(0)).unwrap();
+        assert_eq!(query_game(&app, &game, 1).payout, Uint128::from(5u128));
+        assert_eq!(balance(&app, PLAYER1), 1_007);
+
+        let stats: StatsResponse = app
+            .wrap()
+            .query_wasm_smart(&game, &QueryMsg::Stats {})
+            .unwrap();
+        assert_eq!(
+            stats.stats[0].theoretical_rtp,
+            vec![
+                Decimal::from_str("0.25").unwrap(),
+                Decimal::from_str("0.95").unwrap()
+            ]
+        );
+    }
+
+    #[test]
+    fn payout_overflow_is_an_error() {
+        let wheel = wheel(&[("double", 1, "2"), ("half", 1, "0.5")]);
+
+        assert_eq!(
+            calculate_payout(Uint128::MAX, 1, &wheel).unwrap(),
+            Uint128::MAX / Uint128::from(2u128)
+        );
+        assert_eq!(
+            calculate_payout(Uint128::MAX, 0, &wheel).unwrap_err(),
+            ContractError::PayoutOverflow {
+                bet_size: Uint128::MAX,
+                payout: Decimal::from_str("2").unwrap(),
+            }
+        );
+    }
+
+    #[test]
+    fn house_edge_is_enforced() {
+        let (mut app, game, _) = setup();
+
+        // Even money on a two-segment wheel leaves the house nothing
+        let err = update_config(
+            &mut app,
+            &game,
+            OWNER,
+            UpdateConfigMsg {
+                wheel: Some(wheel(&[("red", 1, "2"), ("black", 1, "2")])),
+                ..Default::default()
+            },
+        )
+        .unwrap_err();
+        assert_eq!(
+            err.downcast::<ContractError>().unwrap(),
+            ContractError::HouseEdgeTooLow {
+                rtp: Decimal::one(),
+                min_house_edge: Decimal::percent(2),
+            }
+        );
+
+        // The best bet sets the edge, no matter how bad the other segments are
+        let err = update_config(
+            &mut app,
+            &game,
+            OWNER,
+            UpdateConfigMsg {
+                wheel: Some(wheel(&[("a", 3, "1.2"), ("b", 1, "3.96")])),
+                ..Default::default()
+            },
+        )
+        .unwrap_err();
+        assert_eq!(
+            err.downcast::<ContractError>().unwrap(),
+            ContractError::HouseEdgeTooLow {
+                rtp: Decimal::percent(99),
+                min_house_edge: Decimal::percent(2),
+            }
+        );
+
+        // Lowering the minimum edge lets the same wheel through
+        update_config(
+            &mut app,
+            &game,
+            OWNER,
+            UpdateConfigMsg {
+                wheel: Some(wheel(&[("a", 3, "1.2"), ("b", 1, "3.96")])),
+                min_house_edge: Some(Decimal::percent(1)),
+                ..Default::default()
+            },
+        )
+        .unwrap();
+
+        // Instantiating with a player-favoured wheel fails as well
+        let mut msg = default_instantiate_msg(&Addr::unchecked("beacon"));
+        msg.wheel = wheel(&[("jackpot", 1, "10"), ("blank", 1, "1")]);
+        let code_id = app.store_code(whale_of_fortune());
+        let err = app
+            .instantiate_contract(code_id, Addr::unchecked(OWNER), &msg, &[], "game", None)
+            .unwrap_err();
+        assert_eq!(
+            err.downcast::<ContractError>().unwrap(),
+            ContractError::HouseEdgeTooLow {
+                rtp: Decimal::from_ratio(5u128, 1u128),
+                min_house_edge: Decimal::percent(2),
+            }
+        );
+    }
+
+    #[test]
+    fn odds_describe_every_segment() {
+        let (mut app, game, _) = setup();
+        update_config(
+            &mut app,
+            &game,
+            OWNER,
+            UpdateConfigMsg {
+                wheel: Some(wheel(&[("1", 3, "1.2"), ("5", 1, "3.8")])),
+                ..Default::default()
+            },
+        )
+        .unwrap();
+
+        let odds: OddsResponse = app
+            .wrap()
+            .query_wasm_smart(
+                &game,
+                &QueryMsg::Odds {
+                    denom: native(DENOM),
+                },
+            )
+            .unwrap();
+        assert_eq!(
+            odds,
+            OddsResponse {
+                segments: vec![
+                    SegmentOdds {
+                        label: "1".to_string(),
+                        weight: 3,
+                        probability: Decimal::percent(75),
+                        payout: Decimal::from_str("1.2").unwrap(),
+                        rtp: Decimal::percent(90),
+                    },
+                    SegmentOdds {
+                        label: "5".to_string(),
+                        weight: 1,
+                        probability: Decimal::percent(25),
+                        payout: Decimal::from_str("3.8").unwrap(),
+                        rtp: Decimal::percent(95),
+                    },
+                ],
+                rtp: Decimal::percent(95),
+                house_edge: Decimal::percent(5),
+            }
+        );
+    }
+
+    fn query_max_bet(app: &App, game: &Addr) -> u128 {
+        let res: MaxBetResponse = app
+            .wrap()
+            .query_wasm_smart(
+                game,
+                &QueryMsg::MaxBet {
+                    denom: native(DENOM),
+                },
+            )
+            .unwrap();
+        res.max_bet.u128()
+    }
+
+    #[test]
+    fn max_bet_follows_exposure_and_pending_games() {
+        let (mut app, game, beacon) = setup();
+
+        // 10% of the 100_000 bankroll, divided by the highest multiplier of 6
+        assert_eq!(query_max_bet(&app, &game), 1_666);
+
+        // A pending bet of 100 on 3x reserves 300 of the bankroll
+        pull(&mut app, &game, PLAYER1, 100, 1).unwrap();
+        assert_eq!(query_max_bet(&app, &game), 1_663);
+        let err = pull(&mut app, &game, OWNER, 1_664, 0).unwrap_err();
+        assert_eq!(
+            err.downcast::<ContractError>().unwrap(),
+            ContractError::BetTooLarge {
+                max_bet: Uint128::from(1_663u128)
+            }
+        );
+
+        // Settling releases the reserve, the lost stake grows the bankroll
+        fulfil(&mut app, &beacon, 0, entropy_for(2)).unwrap();
+        assert_eq!(query_max_bet(&app, &game), 1_668);
+
+        // A refund releases it as well
+        pull(&mut app, &game, PLAYER1, 200, 3).unwrap();
+        assert_eq!(query_max_bet(&app, &game), 1_651);
+        advance_blocks(&mut app, 10);
+        refund(&mut app, &game, PLAYER1, 1).unwrap();
+        assert_eq!(query_max_bet(&app, &game), 1_668);
+
+        // The max bet of the denom still caps the dynamic limit
+        add_denom(
+            &mut app,
+            &game,
+            OWNER,
+            DenomConfig {
+                max_bet: Uint128::from(500u128),
+                ..default_denom_config()
+            },
+        )
+        .unwrap();
+        assert_eq!(query_max_bet(&app, &game), 500);
+
+        for max_exposure in [Decimal::zero(), Decimal::percent(101)] {
+            let err = update_config(
+                &mut app,
+                &game,
+                OWNER,
+                UpdateConfigMsg {
+                    max_exposure: Some(max_exposure),
+                    ..Default::default()
+                },
+            )
+            .unwrap_err();
+            assert_eq!(
+                err.downcast::<ContractError>().unwrap(),
+                ContractError::InvalidMaxExposure {}
+            );
+        }
+    }
+
+    fn query_bankroll(app: &App, game: &Addr) -> (u128, u128, u128) {
+        let res: BankrollResponse = app
+            .wrap()
+            .query_wasm_smart(
+                game,
+                &QueryMsg::Bankroll {
+                    denom: native(DENOM),
+                },
+            )
+            .unwrap();
+        assert_eq!(res.balance, res.reserved + res.free);
+        (res.balance.u128(), res.reserved.u128(), res.free.u128())
+    }
+
+    #[test]
+    fn pending_games_reserve_their_payout() {
+        let (mut app, game, beacon) = setup();
+        assert_eq!(query_bankroll(&app, &game), (100_000, 0, 100_000));
+
+        // Each pending game holds back what it would pay on a win
+        pull(&mut app, &game, PLAYER1, 100, 1).unwrap();
+        pull(&mut app, &game, PLAYER2, 200, 3).unwrap();
+        assert_eq!(query_bankroll(&app, &game), (100_300, 1_500, 98_800));
+
+        // A win pays out of the reserve
+        fulfil(&mut app, &beacon, 0, entropy_for(1)).unwrap();
+        assert_eq!(query_bankroll(&app, &game), (100_000, 1_200, 98_800));
+
+        // An expired game keeps its reserve until the refund goes out
+        advance_blocks(&mut app, 10);
+        fulfil(&mut app, &beacon, 1, entropy_for(0)).unwrap();
+        assert_eq!(query_bankroll(&app, &game), (100_000, 1_200, 98_800));
+        refund(&mut app, &game, PLAYER2, 1).unwrap();
+        assert_eq!(query_bankroll(&app, &game), (99_800, 0, 99_800));
+    }
+
+    #[test]
+    fn bets_outside_the_limits_are_rejected() {
+        let (mut app, game, _) = setup();
+
+        let err = pull(&mut app, &game, PLAYER1, 9, 0).unwrap_err();
+        assert_eq!(
+            err.downcast::<ContractError>().unwrap(),
+            ContractError::BetTooSmall {
+                min_bet: Uint128::from(10u128)
+            }
+        );
+
+        let err = pull(&mut app, &game, OWNER, 10_001, 0).unwrap_err();
+        assert_eq!(
+            err.downcast::<ContractError>().unwrap(),
+            ContractError::BetTooLarge {
+                max_bet: Uint128::from(10_000u128)
+            }
+        );
+    }
+
+    #[test]
+    fn cooldown_limits_how_often_an_address_plays() {
+        let (mut app, game, _) = setup();
+        update_config(
+            &mut app,
+            &game,
+            OWNER,
+            UpdateConfigMsg {
+                rate_limit: Some(RateLimit {
+                    cooldown_blocks: Some(3),
+                }),
+                ..Default::default()
+            },
+        )
+        .unwrap();
+
+        let height = app.block_info().height;
+        pull(&mut app, &game, PLAYER1, 10, 0).unwrap();
+        let err = pull(&mut app, &game, PLAYER1, 10, 0).unwrap_err();
+        assert_eq!(
+            err.downcast::<ContractError>().unwrap(),
+            ContractError::RateLimited {
+                retry_height: height + 3
+            }
+        );
+
+        // Other players are not held up
+        pull(&mut app, &game, PLAYER2, 10, 0).unwrap();
+
+        advance_blocks(&mut app, 2);
+        pull(&mut app, &game, PLAYER1, 10, 0).unwrap_err();
+        advance_blocks(&mut app, 1);
+        pull(&mut app, &game, PLAYER1, 10, 0).unwrap();
+
+        // A cooldown that runs past the last block keeps the address out for good
+        update_config(
+            &mut app,
+            &game,
+            OWNER,
+            UpdateConfigMsg {
+                rate_limit: Some(RateLimit {
+                    cooldown_blocks: Some(u64::MAX),
+                }),
+                ..Default::default()
+            },
+        )
+        .unwrap();
+        let err = pull(&mut app, &game, PLAYER1, 10, 0).unwrap_err();
+        assert_eq!(
+            err.downcast::<ContractError>().unwrap(),
+            ContractError::RateLimited {
+                retry_height: u64::MAX
+            }
+        );
+    }
+
+    #[test]
+    fn wager_cap_limits_how_much_an_address_plays_per_window() {
+        let (mut app, game, _) = setup();
+        add_denom(
+            &mut app,
+            &game,
+            OWNER,
+            DenomConfig {
+                wager_cap: Some(WagerCap {
+                    window_blocks: 10,
+                    amount: Uint128::from(250u128),
+                }),
+                ..default_denom_config()
+            },
+        )
+        .unwrap();
+
+        let height = app.block_info().height;
+        pull(&mut app, &game, PLAYER1, 100, 0).unwrap();
+        pull(&mut app, &game, PLAYER1, 150, 0).unwrap();
+        let err = pull(&mut app, &game, PLAYER1, 10, 0).unwrap_err();
+        assert_eq!(
+            err.downcast::<ContractError>().unwrap(),
+            ContractError::RateLimited {
+                retry_height: height + 10
+            }
+        );
+
+        // A new window starts once the old one has run out
+        advance_blocks(&mut app, 10);
+        pull(&mut app, &game, PLAYER1, 250, 0).unwrap();
+
+        // A window that runs past the last block never starts over
+        add_denom(
+            &mut app,
+            &game,
+            OWNER,
+            DenomConfig {
+                wager_cap: Some(WagerCap {
+                    window_blocks: u64::MAX,
+                    amount: Uint128::from(250u128),
+                }),
+                ..default_denom_config()
+            },
+        )
+        .unwrap();
+        let err = pull(&mut app, &game, PLAYER1, 10, 0).unwrap_err();
+        assert_eq!(
+            err.downcast::<ContractError>().unwrap(),
+            ContractError::RateLimited {
+                retry_height: u64::MAX
+            }
+        );
+
+        let err = update_config(
+            &mut app,
+            &game,
+            OWNER,
+            UpdateConfigMsg {
+                rate_limit: Some(RateLimit {
+                    cooldown_blocks: Some(0),
+                }),
+                ..Default::default()
+            },
+        )
+        .unwrap_err();
+        assert_eq!(
+            err.downcast::<ContractError>().unwrap(),
+            ContractError::InvalidRateLimit {}
+        );
+        let err = add_denom(
+            &mut app,
+            &game,
+            OWNER,
+            DenomConfig {
+                wager_cap: Some(WagerCap {
+                    window_blocks: 0,
+                    amount: Uint128::from(250u128),
+                }),
+                ..default_denom_config()
+            },
+        )
+        .unwrap_err();
+        assert_eq!(
+            err.downcast::<ContractError>().unwrap(),
+            ContractError::InvalidRateLimit {}
+        );
+    }
+
+    #[test]
+    fn invalid_bets_report_what_is_wrong() {
+        let (mut app, game, _) = setup();
+        app.init_modules(|router, _, storage| {
+            router
+                .bank
+                .init_balance(
+                    storage,
+                    &Addr::unchecked(PLAYER1),
+                    vec![coin(1_000, DENOM), coin(1_000, "uusk")],
+                )
+                .unwrap();
+        });
+        let mut pull_with = |funds: &[cosmwasm_std::Coin], bet_number: u128| {
+            app.execute_contract(
+                Addr::unchecked(PLAYER1),
+                game.clone(),
+                &ExecuteMsg::Pull {
+                    bet_number: Uint128::from(bet_number),
+                },
+                funds,
+            )
+            .unwrap_err()
+            .downcast::<ContractError>()
+            .unwrap()
+        };
+
+        // No funds used to panic on info.funds[0]
+        assert_eq!(pull_with(&[], 0), ContractError::InvalidBetAmount {});
+        assert_eq!(
+            pull_with(&[coin(100, DENOM), coin(100, "uusk")], 0),
+            ContractError::InvalidCoin {}
+        );
+        assert_eq!(
+            pull_with(&[coin(100, "uusk")], 0),
+            ContractError::InvalidToken {}
+        );
+        assert_eq!(
+            pull_with(&coins(100, DENOM), 7),
+            ContractError::InvalidBetNumber {}
+        );
+        assert_eq!(
+            pull_with(&coins(100, DENOM), 256),
+            ContractError::InvalidBetNumber {}
+        );
+    }
+
+    #[test]
+    fn owner_deposits_and_withdraws_bankroll() {
+        let (mut app, game, beacon) = setup();
+
+        // Only the owner funds the house, and only in the bet denom
+        let deposit = ExecuteMsg::DepositBankroll {};
+        let err = app
+            .execute_contract(
+                Addr::unchecked(PLAYER1),
+                game.clone(),
+                &deposit,
+                &coins(100, DENOM),
+            )
+            .unwrap_err();
+        assert_eq!(
+            err.downcast::<ContractError>().unwrap(),
+            ContractError::Unauthorized {}
+        );
+        let err = execute_as(&mut app, &game, OWNER, &deposit).unwrap_err();
+        assert_eq!(
+            err.downcast::<ContractError>().unwrap(),
+            ContractError::Payment(PaymentError::NoFunds {})
+        );
+        app.execute_contract(
+            Addr::unchecked(OWNER),
+            game.clone(),
+            &deposit,
+            &coins(50_000, DENOM),
+        )
+        .unwrap();
+
+        // A lost game adds to the result, a pending one counts as paying out in full
+        pull(&mut app, &game, PLAYER1, 100, 1).unwrap();
+        fulfil(&mut app, &beacon, 0, entropy_for(2)).unwrap();
+        pull(&mut app, &game, PLAYER2, 200, 3).unwrap();
+        assert_eq!(query_bankroll(&app, &game), (150_300, 1_200, 149_100));
+
+        // The owner's shares unbond like everyone else's, so the owner can't leave
+        // ahead of a game result
+        let unbond = |shares: u128| ExecuteMsg::UnbondLiquidity {
+            denom: native(DENOM),
+            shares: Uint128::from(shares),
+        };
+        let err = execute_as(&mut app, &game, OWNER, &unbond(150_001)).unwrap_err();
+        assert_eq!(
+            err.downcast::<ContractError>().unwrap(),
+            ContractError::InsufficientShares {
+                available: Uint128::from(150_000u128)
+            }
+        );
+        execute_as(&mut app, &game, OWNER, &unbond(50_000)).unwrap();
+        let err =
+            execute_as(&mut app, &game, OWNER, &ExecuteMsg::WithdrawLiquidity {}).unwrap_err();
+        assert_eq!(
+            err.downcast::<ContractError>().unwrap(),
+            ContractError::NothingToWithdraw {}
+        );
+        advance_blocks(&mut app, 20);
+        let res = execute_as(&mut app, &game, OWNER, &ExecuteMsg::WithdrawLiquidity {}).unwrap();
+        assert_eq!(attribute(&res, "amount"), Some("49700".to_string()));
+
+        let bankroll: BankrollResponse = app
+            .wrap()
+            .query_wasm_smart(
+                &game,
+                &QueryMsg::Bankroll {
+                    denom: native(DENOM),
+                },
+            )
+            .unwrap();
+        assert_eq!(
+            bankroll,
+            BankrollResponse {
+                denom: native(DENOM),
+                balance: Uint128::from(100_600u128),
+                reserved: Uint128::from(1_200u128),
+                free: Uint128::from(99_400u128),
+                deposited: Uint128::from(150_000u128),
+                withdrawn: Uint128::from(49_700u128),
+                profit: Uint128::zero(),
+                loss: Uint128::from(900u128),
+            }
+        );
+
+        // The tracked figure matches what the contract actually holds
+        assert_eq!(balance(&app, game.as_str()), 100_600);
+    }
+
+    fn query_position(app: &App, game: &Addr, address: &str) -> PositionResponse {
+        app.wrap()
+            .query_wasm_smart(
+                game,
+                &QueryMsg::Position {
+                    address: address.to_string(),
+                    denom: native(DENOM),
+                },
+            )
+            .unwrap()
+    }
+
+    #[test]
+    fn liquidity_providers_share_in_the_house_result() {
+        let (mut app, game, beacon) = setup();
+
+        // The instantiation funds are the owner's shares
+        let vault: VaultResponse = app
+            .wrap()
+            .query_wasm_smart(
+                &game,
+                &QueryMsg::Vault {
+                    denom: native(DENOM),
+                },
+            )
+            .unwrap();
+        assert_eq!(vault.total_shares, Uint128::from(100_000u128));
+        assert_eq!(vault.share_price, Decimal::one());
+        assert!(vault.pays_entropy_fees);
+        assert_eq!(vault.entropy_fees, Uint128::zero());
+        assert_eq!(
+            query_position(&app, &game, OWNER).shares,
+            vault.total_shares
+        );
+
+        app.execute_contract(
+            Addr::unchecked(PLAYER2),
+            game.clone(),
+            &ExecuteMsg::DepositLiquidity {},
+            &coins(500, DENOM),
+        )
+        .unwrap();
+        assert_eq!(
+            query_position(&app, &game, PLAYER2).shares,
+            Uint128::from(500u128)
+        );
+
+        // A lost bet of 402 lifts every share by 0.4%
+        pull(&mut app, &game, PLAYER1, 402, 1).unwrap();
+        fulfil(&mut app, &beacon, 0, entropy_for(2)).unwrap();
+        let vault: VaultResponse = app
+            .wrap()
+            .query_wasm_smart(
+                &game,
+                &QueryMsg::Vault {
+                    denom: native(DENOM),
+                },
+            )
+            .unwrap();
+        assert_eq!(vault.share_price, Decimal::from_str("1.004").unwrap());
+        assert_eq!(
+            query_position(&app, &game, PLAYER2).value,
+            Uint128::from(502u128)
+        );
+
+        // Shares have to unbond before they can be withdrawn
+        let err = execute_as(
+            &mut app,
+            &game,
+            PLAYER2,
+            &ExecuteMsg::UnbondLiquidity {
+                denom: native(DENOM),
+                shares: Uint128::from(501u128),
+            },
+        )
+        .unwrap_err();
+        assert_eq!(
+            err.downcast::<ContractError>().unwrap(),
+            ContractError::InsufficientShares {
+                available: Uint128::from(500u128)
+            }
+        );
+        let height = app.block_info().height;
+        execute_as(
+            &mut app,
+            &game,
+            PLAYER2,
+            &ExecuteMsg::UnbondLiquidity {
+                denom: native(DENOM),
+                shares: Uint128::from(500u128),
+            },
+        )
+        .unwrap();
+        let position = query_position(&app, &game, PLAYER2);
+        assert_eq!(position.shares, Uint128::zero());
+        assert_eq!(
+            position.unbonding[0].release_at,
+            Expiration::AtHeight(height + 20)
+        );
+
+        let err =
+            execute_as(&mut app, &game, PLAYER2, &ExecuteMsg::WithdrawLiquidity {}).unwrap_err();
+        assert_eq!(
+            err.downcast::<ContractError>().unwrap(),
+            ContractError::NothingToWithdraw {}
+        );
+
+        advance_blocks(&mut app, 20);
+        execute_as(&mut app, &game, PLAYER2, &ExecuteMsg::WithdrawLiquidity {}).unwrap();
+        assert_eq!(balance(&app, PLAYER2), 1_002);
+        assert!(query_position(&app, &game, PLAYER2).unbonding.is_empty());
+
+        // While a game is pending, shares are worth what its reserved payout leaves over
+        pull(&mut app, &game, PLAYER1, 100, 3).unwrap();
+        assert_eq!(query_bankroll(&app, &game), (100_500, 600, 99_900));
+        assert_eq!(
+            query_position(&app, &game, OWNER).value,
+            Uint128::from(99_900u128)
+        );
+        execute_as(
+            &mut app,
+            &game,
+            OWNER,
+            &ExecuteMsg::UnbondLiquidity {
+                denom: native(DENOM),
+                shares: Uint128::from(50_000u128),
+            },
+        )
+        .unwrap();
+        advance_blocks(&mut app, 20);
+        let res = execute_as(&mut app, &game, OWNER, &ExecuteMsg::WithdrawLiquidity {}).unwrap();
+        assert_eq!(attribute(&res, "amount"), Some("49950".to_string()));
+
+        // Once the game is refunded, what was reserved for it goes to the shares that stayed
+        refund(&mut app, &game, PLAYER1, 1).unwrap();
+        assert_eq!(query_bankroll(&app, &game), (50_450, 0, 50_450));
+        assert_eq!(
+            query_position(&app, &game, OWNER).value,
+            Uint128::from(50_450u128)
+        );
+    }
+
+    #[test]
+    fn whitelisted_denoms_are_played_on_their_own_terms() {
+        let (mut app, game, beacon) = setup();
+        app.init_modules(|router, _, storage| {
+            for addr in [OWNER, PLAYER1] {
+                router
+                    .bank
+                    .init_balance(
+                        storage,
+                        &Addr::unchecked(addr),
+                        vec![coin(1_000_000, DENOM), coin(100_000, "uusk")],
+                    )
+                    .unwrap();
+            }
+        });
+        let usk = DenomConfig {
+            denom: native("uusk"),
+            min_bet: Uint128::from(100u128),
+            max_bet: Uint128::from(5_000u128),
+            fee: FeeModel::Flat(Uint128::from(5u128)),
+            wheel: Some(wheel(&[("even", 1, "1.9"), ("odd", 1, "1.9")])),
+            wager_cap: None,
+        };
+        let pull_usk = |app: &mut App, amount: u128, bet_number: u128| {
+            app.execute_contract(
+                Addr::unchecked(PLAYER1),
+                game.clone(),
+                &ExecuteMsg::Pull {
+                    bet_number: Uint128::from(bet_number),
+                },
+                &coins(amount, "uusk"),
+            )
+        };
+
+        // Only the owner manages the whitelist
+        let err = add_denom(&mut app, &game, PLAYER1, usk.clone()).unwrap_err();
+        assert_eq!(
+            err.downcast::<ContractError>().unwrap(),
+            ContractError::Unauthorized {}
+        );
+        let err = pull_usk(&mut app, 100, 0).unwrap_err();
+        assert_eq!(
+            err.downcast::<ContractError>().unwrap(),
+            ContractError::InvalidToken {}
+        );
+        let res = add_denom(&mut app, &game, OWNER, usk.clone()).unwrap();
+        assert_eq!(attribute(&res, "fee"), Some("flat:5".to_string()));
+        let denoms: DenomsResponse = app
+            .wrap()
+            .query_wasm_smart(&game, &QueryMsg::Denoms {})
+            .unwrap();
+        assert_eq!(denoms.denoms, vec![default_denom_config(), usk.clone()]);
+
+        // The new denom has a bankroll of its own, which starts out empty
+        let err = pull_usk(&mut app, 100, 0).unwrap_err();
+        assert_eq!(
+            err.downcast::<ContractError>().unwrap(),
+            ContractError::BetTooLarge {
+                max_bet: Uint128::zero()
+            }
+        );
+        app.execute_contract(
+            Addr::unchecked(OWNER),
+            game.clone(),
+            &ExecuteMsg::DepositBankroll {},
+            &coins(20_000, "uusk"),
+        )
+        .unwrap();
+        let max_bet: MaxBetResponse = app
+            .wrap()
+            .query_wasm_smart(
+                &game,
+                &QueryMsg::MaxBet {
+                    denom: native("uusk"),
+                },
+            )
+            .unwrap();
+        assert_eq!(max_bet.max_bet, Uint128::from(1_052u128));
+
+        // Its own limits and wheel apply
+        let err = pull_usk(&mut app, 99, 0).unwrap_err();
+        assert_eq!(
+            err.downcast::<ContractError>().unwrap(),
+            ContractError::BetTooSmall {
+                min_bet: Uint128::from(100u128)
+            }
+        );
+        let err = pull_usk(&mut app, 100, 2).unwrap_err();
+        assert_eq!(
+            err.downcast::<ContractError>().unwrap(),
+            ContractError::InvalidBetNumber {}
+        );
+        pull_usk(&mut app, 100, 1).unwrap();
+        let bankroll: BankrollResponse = app
+            .wrap()
+            .query_wasm_smart(
+                &game,
+                &QueryMsg::Bankroll {
+                    denom: native("uusk"),
+                },
+            )
+            .unwrap();
+        assert_eq!(bankroll.balance, Uint128::from(20_095u128));
+        assert_eq!(bankroll.reserved, Uint128::from(180u128));
+        assert_eq!(query_bankroll(&app, &game), (100_000, 0, 100_000));
+
+        // A stricter house edge has to hold for every whitelisted wheel
+        let err = update_config(
+            &mut app,
+            &game,
+            OWNER,
+            UpdateConfigMsg {
+                min_house_edge: Some(Decimal::percent(6)),
+                ..Default::default()
+            },
+        )
+        .unwrap_err();
+        assert_eq!(
+            err.downcast::<ContractError>().unwrap(),
+            ContractError::HouseEdgeTooLow {
+                rtp: Decimal::percent(95),
+                min_house_edge: Decimal::percent(6),
+            }
+        );
+
+        // Removing the denom stops new bets, the pending one still settles
+        let remove = ExecuteMsg::RemoveDenom {
+            denom: native("uusk"),
+        };
+        execute_as(&mut app, &game, OWNER, &remove).unwrap();
+        let err = execute_as(&mut app, &game, OWNER, &remove).unwrap_err();
+        assert_eq!(
+            err.downcast::<ContractError>().unwrap(),
+            ContractError::UnknownDenom {
+                denom: "uusk".to_string()
+            }
+        );
+        let err = pull_usk(&mut app, 100, 1).unwrap_err();
+        assert_eq!(
+            err.downcast::<ContractError>().unwrap(),
+            ContractError::InvalidToken {}
+        );
+
+        let entropy = (0..=u8::MAX)
+            .map(|seed| vec![seed; 64])
+            .find(|entropy| get_outcome_from_entropy(entropy, 2) == 1)
+            .unwrap();
+        let res = fulfil(&mut app, &beacon, 0, entropy).unwrap();
+        assert_eq!(attribute(&res, "segment"), Some("odd".to_string()));
+        assert_eq!(query_game(&app, &game, 0).token, native("uusk"));
+        assert_eq!(
+            app.wrap().query_balance(PLAYER1, "uusk").unwrap().amount,
+            Uint128::from(100_080u128)
+        );
+
+        // Player statistics are kept apart per denom
+        let stats: PlayerStatsResponse = app
+            .wrap()
+            .query_wasm_smart(
+                &game,
+                &QueryMsg::PlayerStats {
+                    address: PLAYER1.to_string(),
+                    denom: native("uusk"),
+                },
+            )
+            .unwrap();
+        assert_eq!(
+            (stats.games_played, stats.total_wagered, stats.total_won),
+            (1, Uint128::from(95u128), Uint128::from(180u128))
+        );
+        assert_eq!(query_player_stats(&app, &game, PLAYER1).games_played, 0);
+    }
+
+    fn cw20_token() -> Box<dyn Contract<Empty>> {
+        Box::new(ContractWrapper::new(
+            cw20_base::contract::execute,
+            cw20_base::contract::instantiate,
+            cw20_base::contract::query,
+        ))
+    }
+
+    // Instantiates a cw20-base token held by the owner and both players
+    fn setup_cw20(app: &mut App) -> Addr {
+        let code_id = app.store_code(cw20_token());
+        let initial_balances = [(OWNER, 1_000_000u128), (PLAYER1, 1_000), (PLAYER2, 1_000)]
+            .map(|(address, amount)| Cw20Coin {
+                address: address.to_string(),
+                amount: Uint128::from(amount),
+            })
+            .to_vec();
+        app.instantiate_contract(
+            code_id,
+            Addr::unchecked(OWNER),
+            &cw20_base::msg::InstantiateMsg {
+                name: "Whale Token".to_string(),
+                symbol: "WHALE".to_string(),
+                decimals: 6,
+                initial_balances,
+                mint: None,
+                marketing: None,
+            },
+            &[],
+            "whale-token",
+            None,
+        )
+        .unwrap()
+    }
+
+    fn send_cw20(
+        app: &mut App,
+        token: &Addr,
+        game: &Addr,
+        sender: &str,
+        amount: u128,
+        msg: &ReceiveMsg,
+    ) -> anyhow::Result<AppResponse> {
+        app.execute_contract(
+            Addr::unchecked(sender),
+            token.clone(),
+            &Cw20ExecuteMsg::Send {
+                contract: game.to_string(),
+                amount: Uint128::from(amount),
+                msg: to_binary(msg).unwrap(),
+            },
+            &[],
+        )
+    }
+
+    fn cw20_balance(app: &App, token: &Addr, address: &str) -> u128 {
+        let res: BalanceResponse = app
+            .wrap()
+            .query_wasm_smart(
+                token,
+                &Cw20QueryMsg::Balance {
+                    address: address.to_string(),
+                },
+            )
+            .unwrap();
+        res.balance.u128()
+    }
+
+    #[test]
+    fn cw20_bets_are_paid_out_and_refunded_in_the_token() {
+        let (mut app, game, beacon) = setup();
+        let token = setup_cw20(&mut app);
+        let whale = AssetInfo::Cw20(token.clone());
+        let bet = |bet_number: u128| ReceiveMsg::Pull {
+            bet_number: Uint128::from(bet_number),
+        };
+
+        // Tokens only count once whitelisted, and only when the token contract sends them
+        let err = send_cw20(&mut app, &token, &game, PLAYER1, 100, &bet(1)).unwrap_err();
+        assert_eq!(
+            err.downcast::<ContractError>().unwrap(),
+            ContractError::InvalidToken {}
+        );
+        add_denom(
+            &mut app,
+            &game,
+            OWNER,
+            DenomConfig {
+                denom: whale.clone(),
+                ..default_denom_config()
+            },
+        )
+        .unwrap();
+        let forged = ExecuteMsg::Receive(Cw20ReceiveMsg {
+            sender: PLAYER1.to_string(),
+            amount: Uint128::from(100u128),
+            msg: to_binary(&bet(1)).unwrap(),
+        });
+        let err = execute_as(&mut app, &game, PLAYER1, &forged).unwrap_err();
+        assert_eq!(
+            err.downcast::<ContractError>().unwrap(),
+            ContractError::InvalidToken {}
+        );
+
+        // The owner funds the token bankroll through the token contract
+        send_cw20(
+            &mut app,
+            &token,
+            &game,
+            OWNER,
+            100_000,
+            &ReceiveMsg::DepositBankroll {},
+        )
+        .unwrap();
+
+        // A winning bet is paid out in the token
+        send_cw20(&mut app, &token, &game, PLAYER1, 100, &bet(1)).unwrap();
+        fulfil(&mut app, &beacon, 0, entropy_for(1)).unwrap();
+        assert_eq!(query_game(&app, &game, 0).token, whale);
+        assert_eq!(cw20_balance(&app, &token, PLAYER1), 1_200);
+
+        // An unanswered bet is refunded in the token
+        send_cw20(&mut app, &token, &game, PLAYER2, 200, &bet(3)).unwrap();
+        assert_eq!(cw20_balance(&app, &token, PLAYER2), 800);
+        advance_blocks(&mut app, 10);
+        refund(&mut app, &game, PLAYER2, 1).unwrap();
+        assert_eq!(cw20_balance(&app, &token, PLAYER2), 1_000);
+
+        // The token bankroll tracks what the contract holds, the native one is untouched
+        let bankroll: BankrollResponse = app
+            .wrap()
+            .query_wasm_smart(
+                &game,
+                &QueryMsg::Bankroll {
+                    denom: whale.clone(),
+                },
+            )
+            .unwrap();
+        assert_eq!(bankroll.balance, Uint128::from(99_800u128));
+        assert_eq!(cw20_balance(&app, &token, game.as_str()), 99_800);
+        assert_eq!(query_bankroll(&app, &game), (100_000, 0, 100_000));
+
+        // Liquidity comes and goes in the token as well
+        send_cw20(
+            &mut app,
+            &token,
+            &game,
+            PLAYER1,
+            499,
+            &ReceiveMsg::DepositLiquidity {},
+        )
+        .unwrap();
+        execute_as(
+            &mut app,
+            &game,
+            PLAYER1,
+            &ExecuteMsg::UnbondLiquidity {
+                denom: whale,
+                shares: Uint128::from(500u128),
+            },
+        )
+        .unwrap();
+        advance_blocks(&mut app, 20);
+        execute_as(&mut app, &game, PLAYER1, &ExecuteMsg::WithdrawLiquidity {}).unwrap();
+        assert_eq!(cw20_balance(&app, &token, PLAYER1), 1_200);
+    }
+
+    fn query_accrued_fees(app: &App, game: &Addr, address: &str) -> Vec<AccruedFee> {
+        let res: AccruedFeesResponse = app
+            .wrap()
+            .query_wasm_smart(
+                game,
+                &QueryMsg::AccruedFees {
+                    address: address.to_string(),
+                },
+            )
+            .unwrap();
+        res.fees
+    }
+
+    #[test]
+    fn fees_accrue_to_weighted_recipients_until_claimed() {
+        let (mut app, game, beacon) = setup();
+        update_config(
+            &mut app,
+            &game,
+            OWNER,
+            UpdateConfigMsg {
+                fee_recipients: Some(vec![
+                    FeeRecipient {
+                        address: Addr::unchecked("treasury"),
+                        weight: 3,
+                    },
+                    FeeRecipient {
+                        address: Addr::unchecked("dev"),
+                        weight: 1,
+                    },
+                ]),
+                ..Default::default()
+            },
+        )
+        .unwrap();
+        let set_fee = |app: &mut App, fee: FeeModel| {
+            add_denom(
+                app,
+                &game,
+                OWNER,
+                DenomConfig {
+                    fee,
+                    ..default_denom_config()
+                },
+            )
+            .unwrap();
+        };
+
+        // 5% of the stake is taken up front and 95 is wagered on a 3x win
+        set_fee(&mut app, FeeModel::WagerBps(500));
+        let res = pull(&mut app, &game, PLAYER1, 100, 1).unwrap();
+        assert_eq!(attribute(&res, "fee"), Some("5".to_string()));
+        let res = fulfil(&mut app, &beacon, 0, entropy_for(1)).unwrap();
+        assert_eq!(attribute(&res, "payout"), Some("285".to_string()));
+        assert_eq!(attribute(&res, "fee"), Some("5".to_string()));
+
+        // 10% of the 200 won is taken from the payout
+        set_fee(&mut app, FeeModel::NetWinningsBps(1_000));
+        let res = pull(&mut app, &game, PLAYER1, 100, 1).unwrap();
+        assert_eq!(attribute(&res, "fee"), Some("0".to_string()));
+        let res = fulfil(&mut app, &beacon, 1, entropy_for(1)).unwrap();
+        assert_eq!(attribute(&res, "payout"), Some("280".to_string()));
+        let settled = query_game(&app, &game, 1);
+        assert_eq!(
+            (settled.bet_size, settled.fee, settled.payout),
+            (
+                Uint128::from(100u128),
+                Uint128::from(20u128),
+                Uint128::from(280u128)
+            )
+        );
+
+        // A flat fee is kept on a loss, but refunded with the stake
+        set_fee(&mut app, FeeModel::Flat(Uint128::from(7u128)));
+        pull(&mut app, &game, PLAYER1, 100, 0).unwrap();
+        pull(&mut app, &game, PLAYER2, 100, 6).unwrap();
+        let res = fulfil(&mut app, &beacon, 2, entropy_for(1)).unwrap();
+        assert_eq!(attribute(&res, "fee"), Some("7".to_string()));
+        advance_blocks(&mut app, 10);
+        let res = refund(&mut app, &game, PLAYER2, 3).unwrap();
+        assert_eq!(attribute(&res, "refund"), Some("100".to_string()));
+        assert_eq!(
+            app.wrap().query_balance(PLAYER1, DENOM).unwrap().amount,
+            Uint128::from(1_265u128)
+        );
+        assert_eq!(
+            app.wrap().query_balance(PLAYER2, DENOM).unwrap().amount,
+            Uint128::from(1_000u128)
+        );
+
+        // Fees are split 3:1, with what rounding leaves over going to the first recipient
+        assert_eq!(
+            query_accrued_fees(&app, &game, "treasury"),
+            vec![AccruedFee {
+                denom: native(DENOM),
+                amount: Uint128::from(25u128),
+            }]
+        );
+        assert_eq!(
+            query_accrued_fees(&app, &game, "dev"),
+            vec![AccruedFee {
+                denom: native(DENOM),
+                amount: Uint128::from(7u128),
+            }]
+        );
+        let res: StatsResponse = app
+            .wrap()
+            .query_wasm_smart(&game, &QueryMsg::Stats {})
+            .unwrap();
+        assert_eq!(res.stats[0].stats.fees_collected, Uint128::from(32u128));
+
+        // Unclaimed fees are held next to the bankroll
+        let contract_balance = |app: &App| {
+            app.wrap()
+                .query_balance(&game, DENOM)
+                .unwrap()
+                .amount
+                .u128()
+        };
+        assert_eq!(contract_balance(&app), query_bankroll(&app, &game).0 + 32);
+
+        let res = execute_as(&mut app, &game, "treasury", &ExecuteMsg::ClaimFees {}).unwrap();
+        assert_eq!(attribute(&res, "amount"), Some("25".to_string()));
+        assert_eq!(
+            app.wrap().query_balance("treasury", DENOM).unwrap().amount,
+            Uint128::from(25u128)
+        );
+        assert_eq!(query_accrued_fees(&app, &game, "treasury"), vec![]);
+        assert_eq!(contract_balance(&app), query_bankroll(&app, &game).0 + 7);
+        let err = execute_as(&mut app, &game, "treasury", &ExecuteMsg::ClaimFees {}).unwrap_err();
+        assert_eq!(
+            err.downcast::<ContractError>().unwrap(),
+            ContractError::NothingToClaim {}
+        );
+    }
+
+    #[test]
+    fn fee_terms_are_validated() {
+        let (mut app, game, _beacon) = setup();
+
+        let add_fee = |app: &mut App, fee: FeeModel| {
+            add_denom(
+                app,
+                &game,
+                OWNER,
+                DenomConfig {
+                    fee,
+                    ..default_denom_config()
+                },
+            )
+            .unwrap_err()
+            .downcast::<ContractError>()
+            .unwrap()
+        };
+        assert_eq!(
+            add_fee(&mut app, FeeModel::NetWinningsBps(10_000)),
+            ContractError::InvalidFeeBps { bps: 10_000 }
+        );
+        assert_eq!(
+            add_fee(&mut app, FeeModel::Flat(Uint128::from(10u128))),
+            ContractError::InvalidFee {
+                fee_amount: Uint128::from(10u128),
+                min_bet: Uint128::from(10u128),
+            }
+        );
+
+        for fee_recipients in [
+            vec![],
+            vec![FeeRecipient {
+                address: Addr::unchecked("treasury"),
+                weight: 0,
+            }],
+        ] {
+            let err = update_config(
+                &mut app,
+                &game,
+                OWNER,
+                UpdateConfigMsg {
+                    fee_recipients: Some(fee_recipients),
+                    ..Default::default()
+                },
+            )
+            .unwrap_err();
+            assert_eq!(
+                err.downcast::<ContractError>().unwrap(),
+                ContractError::InvalidFeeRecipients {}
+            );
+        }
+    }
+
+    #[test]
+    fn instantiate_rejects_invalid_config() {
+        let (mut app, _game, beacon) = setup();
+        let code_id = app.store_code(whale_of_fortune());
+
+        let with_denom = |denom_config: DenomConfig| InstantiateMsg {
+            denoms: vec![denom_config],
+            ..default_instantiate_msg(&beacon)
+        };
+        for (msg, expected) in [
+            (
+                with_denom(DenomConfig {
+                    denom: native(""),
+                    ..default_denom_config()
+                }),
+                ContractError::InvalidDenom {},
+            ),
+            (
+                InstantiateMsg {
+                    beacon_fee_denom: "".into(),
+                    ..default_instantiate_msg(&beacon)
+                },
+                ContractError::InvalidDenom {},
+            ),
+            (
+                with_denom(DenomConfig {
+                    min_bet: Uint128::zero(),
+                    ..default_denom_config()
+                }),
+                ContractError::InvalidBetLimits {
+                    min_bet: Uint128::zero(),
+                    max_bet: Uint128::from(10_000u128),
+                },
+            ),
+            (
+                with_denom(DenomConfig {
+                    min_bet: Uint128::from(10_001u128),
+                    ..default_denom_config()
+                }),
+                ContractError::InvalidBetLimits {
+                    min_bet: Uint128::from(10_001u128),
+                    max_bet: Uint128::from(10_000u128),
+                },
+            ),
+            (
+                with_denom(DenomConfig {
+                    fee: FeeModel::Flat(Uint128::from(10u128)),
+                    ..default_denom_config()
+                }),
+                ContractError::InvalidFee {
+                    fee_amount: Uint128::from(10u128),
+                    min_bet: Uint128::from(10u128),
+                },
+            ),
+        ] {
+            let err = app
+                .instantiate_contract(code_id, Addr::unchecked(OWNER), &msg, &[], "game", None)
+                .unwrap_err();
+            assert_eq!(err.downcast::<ContractError>().unwrap(), expected);
+        }
+
+        // Denoms can all be added later
+        let msg = InstantiateMsg {
+            denoms: vec![],
+            ..default_instantiate_msg(&beacon)
+        };
+        let empty = app
+            .instantiate_contract(code_id, Addr::unchecked(OWNER), &msg, &[], "game", None)
+            .unwrap();
+        add_denom(&mut app, &empty, OWNER, default_denom_config()).unwrap();
+
+        // Funds in a denom without a bankroll could never be withdrawn
+        app.init_modules(|router, _, storage| {
+            router
+                .bank
+                .init_balance(storage, &Addr::unchecked(OWNER), coins(1_000, "uusk"))
+                .unwrap();
+        });
+        let err = app
+            .instantiate_contract(
+                code_id,
+                Addr::unchecked(OWNER),
+                &default_instantiate_msg(&beacon),
+                &coins(1_000, "uusk"),
+                "game",
+                None,
+            )
+            .unwrap_err();
+        assert_eq!(
+            err.downcast::<ContractError>().unwrap(),
+            ContractError::InvalidToken {}
+        );
+    }
+
+    #[test]
+    fn entropy_fees_come_out_of_a_whitelisted_native_bankroll() {
+        let (mut app, _game, beacon) = setup();
+        let token = setup_cw20(&mut app);
+
+        // A game that only takes the token and holds nothing native
+        let code_id = app.store_code(whale_of_fortune());
+        let msg = InstantiateMsg {
+            denoms: vec![DenomConfig {
+                denom: AssetInfo::Cw20(token.clone()),
+                ..default_denom_config()
+            }],
+            ..default_instantiate_msg(&beacon)
+        };
+        let game = app
+            .instantiate_contract(code_id, Addr::unchecked(OWNER), &msg, &[], "game", None)
+            .unwrap();
+        send_cw20(
+            &mut app,
+            &token,
+            &game,
+            OWNER,
+            100_000,
+            &ReceiveMsg::DepositBankroll {},
+        )
+        .unwrap();
+        let bet = ReceiveMsg::Pull {
+            bet_number: Uint128::one(),
+        };
+
+        // Without an entropy fee there is nothing to pay for
+        send_cw20(&mut app, &token, &game, PLAYER1, 100, &bet).unwrap();
+        fulfil(&mut app, &beacon, 0, entropy_for(1)).unwrap();
+        assert_eq!(cw20_balance(&app, &token, PLAYER1), 1_200);
+
+        // A fee has to be covered by the free bankroll of the beacon's fee denom
+        app.execute_contract(
+            Addr::unchecked(OWNER),
+            beacon.clone(),
+            &MockBeaconExecuteMsg::SetFee { fee: 50 },
+            &[],
+        )
+        .unwrap();
+        let err = send_cw20(&mut app, &token, &game, PLAYER1, 100, &bet).unwrap_err();
+        assert_eq!(
+            err.downcast::<ContractError>().unwrap(),
+            ContractError::BeaconFeeNotCovered {
+                denom: DENOM.to_string(),
+                fee: Uint128::from(50u128),
+                free: Uint128::zero(),
+            }
+        );
+
+        add_denom(&mut app, &game, OWNER, default_denom_config()).unwrap();
+        let deposit = |app: &mut App, amount: u128| {
+            app.execute_contract(
+                Addr::unchecked(OWNER),
+                game.clone(),
+                &ExecuteMsg::DepositBankroll {},
+                &coins(amount, DENOM),
+            )
+            .unwrap();
+        };
+        deposit(&mut app, 40);
+        let err = send_cw20(&mut app, &token, &game, PLAYER1, 100, &bet).unwrap_err();
+        assert_eq!(
+            err.downcast::<ContractError>().unwrap(),
+            ContractError::BeaconFeeNotCovered {
+                denom: DENOM.to_string(),
+                fee: Uint128::from(50u128),
+                free: Uint128::from(40u128),
+            }
+        );
+        deposit(&mut app, 60);
+        send_cw20(&mut app, &token, &game, PLAYER1, 100, &bet).unwrap();
+        assert_eq!(query_bankroll(&app, &game), (50, 0, 50));
+        assert_eq!(
+            app.wrap().query_balance(&beacon, DENOM).unwrap().amount,
+            Uint128::from(50u128)
+        );
+
+        // The native vault carries the token game's entropy cost, and says so
+        let vault = |denom: AssetInfo| -> VaultResponse {
+            app.wrap()
+                .query_wasm_smart(&game, &QueryMsg::Vault { denom })
+                .unwrap()
+        };
+        let native_vault = vault(native(DENOM));
+        assert!(native_vault.pays_entropy_fees);
+        assert_eq!(native_vault.entropy_fees, Uint128::from(50u128));
+        assert_eq!(native_vault.share_price, Decimal::percent(50));
+        let token_vault = vault(AssetInfo::Cw20(token.clone()));
+        assert!(!token_vault.pays_entropy_fees);
+        assert_eq!(token_vault.entropy_fees, Uint128::zero());
+    }
+
+    #[test]
+    fn wiped_out_bankroll_takes_deposits_once_its_shares_are_gone() {
+        let (mut app, game, beacon) = setup();
+        let set_fee = |app: &mut App, fee: u64| {
+            app.execute_contract(
+                Addr::unchecked(OWNER),
+                beacon.clone(),
+                &MockBeaconExecuteMsg::SetFee { fee },
+                &[],
+            )
+            .unwrap();
+        };
+        let deposit = |app: &mut App| {
+            app.execute_contract(
+                Addr::unchecked(PLAYER2),
+                game.clone(),
+                &ExecuteMsg::DepositLiquidity {},
+                &coins(500, DENOM),
+            )
+        };
+
+        // The entropy fee takes all that is free and the win takes the rest
+        set_fee(&mut app, 99_800);
+        pull(&mut app, &game, PLAYER1, 100, 1).unwrap();
+        fulfil(&mut app, &beacon, 0, entropy_for(1)).unwrap();
+        set_fee(&mut app, 0);
+        assert_eq!(query_bankroll(&app, &game), (0, 0, 0));
+        assert_eq!(
+            query_position(&app, &game, OWNER).shares,
+            Uint128::from(100_000u128)
+        );
+
+        // A new deposit would be split with the worthless shares
+        let err = deposit(&mut app).unwrap_err();
+        assert_eq!(
+            err.downcast::<ContractError>().unwrap(),
+            ContractError::BankrollWipedOut {
+                shares: Uint128::from(100_000u128)
+            }
+        );
+
+        execute_as(
+            &mut app,
+            &game,
+            OWNER,
+            &ExecuteMsg::UnbondLiquidity {
+                denom: native(DENOM),
+                shares: Uint128::from(100_000u128),
+            },
+        )
+        .unwrap();
+        advance_blocks(&mut app, 20);
+        let res = execute_as(&mut app, &game, OWNER, &ExecuteMsg::WithdrawLiquidity {}).unwrap();
+        assert_eq!(attribute(&res, "amount"), Some("0".to_string()));
+
+        // With the old shares burnt the next deposit buys shares one to one again
+        deposit(&mut app).unwrap();
+        let position = query_position(&app, &game, PLAYER2);
+        assert_eq!(position.shares, Uint128::from(500u128));
+        assert_eq!(position.value, Uint128::from(500u128));
+    }
+
+    #[test]
+    fn pending_games_reserve_at_least_their_wager() {
+        let (mut app, game, _beacon) = setup();
+        update_config(
+            &mut app,
+            &game,
+            OWNER,
+            UpdateConfigMsg {
+                wheel: Some(wheel(&[("half", 1, "0.5"), ("big", 1, "1.4")])),
+                ..Default::default()
+            },
+        )
+        .unwrap();
+
+        // A consolation segment pays back less than the wager a refund would return
+        pull(&mut app, &game, PLAYER1, 1_000, 0).unwrap();
+        assert_eq!(query_bankroll(&app, &game), (101_000, 1_000, 100_000));
+
+        // Everything free can be withdrawn and the refund is still covered
+        execute_as(
+            &mut app,
+            &game,
+            OWNER,
+            &ExecuteMsg::UnbondLiquidity {
+                denom: native(DENOM),
+                shares: Uint128::from(100_000u128),
+            },
+        )
+        .unwrap();
+        advance_blocks(&mut app, 20);
+        execute_as(&mut app, &game, OWNER, &ExecuteMsg::WithdrawLiquidity {}).unwrap();
+        assert_eq!(query_bankroll(&app, &game), (1_000, 1_000, 0));
+        refund(&mut app, &game, PLAYER1, 0).unwrap();
+        assert_eq!(balance(&app, PLAYER1), 1_000);
+        assert_eq!(query_bankroll(&app, &game), (0, 0, 0));
+    }
+
+    #[test]
+    fn migrate_only_accepts_the_current_layout() {
+        let mut deps = mock_dependencies();
+
+        // The baseline release kept a different storage layout
+        cw2::set_contract_version(&mut deps.storage, "crates.io:Spin-the-whale", "0.1.0").unwrap();
+        let err = migrate(deps.as_mut(), mock_env(), MigrateMsg {}).unwrap_err();
+        assert_eq!(
+            err,
+            ContractError::CannotMigrate {
+                contract: "crates.io:Spin-the-whale".to_string(),
+                version: "0.1.0".to_string(),
+            }
+        );
+
+        cw2::set_contract_version(
+            &mut deps.storage,
+            "crates.io:cw20-base",
+            env!("CARGO_PKG_VERSION"),
+        )
+        .unwrap();
+        let err = migrate(deps.as_mut(), mock_env(), MigrateMsg {}).unwrap_err();
+        assert!(matches!(err, ContractError::CannotMigrate { .. }));
+
+        cw2::set_contract_version(
+            &mut deps.storage,
+            "crates.io:Spin-the-whale",
+            env!("CARGO_PKG_VERSION"),
+        )
+        .unwrap();
+        let res = migrate(deps.as_mut(), mock_env(), MigrateMsg {}).unwrap();
+        assert!(res
+            .attributes
+            .iter()
+            .any(|attr| attr.key == "version" && attr.value == env!("CARGO_PKG_VERSION")));
+    }
+}
+
 // mod tests {
 //     use super::*;
 //     use crate::contract::{self, instantiate, execute, query, execute_entropy_beacon_pull, execute_spin, execute_validate_bet};
@@ -23,8 +2776,6 @@
 //     use cosmwasm_std::testing::{mock_dependencies_with_balance, mock_dependencies, mock_env, mock_info, MockQuerier};
 //     use cosmwasm_std::{coins, QuerierWrapper, Empty, BankQuery, Coin, Uint128, BalanceResponse, QueryRequest, QueryResponse};
 
-
-
 // #[test]
 // fn env_testing() {
 //     //TODO: Need to query contract addr balance
@@ -34,27 +2785,23 @@
 //     let msg = InstantiateMsg { };
 //     let env = mock_env();
 
-//     let balance = env.contract.address; 
+//     let balance = env.contract.address;
 
-//     // Query contrat address balance 
+//     // Query contrat address balance
 //     let bankroll = BankQuery::Balance {
-//         address: "creator".to_string(), 
-//         denom: "ukuji".to_string(), 
+//         address: "creator".to_string(),
+//         denom: "ukuji".to_string(),
 //        };
 
 //     let query:QueryRequest<_> = QueryRequest::Bank(bankroll);
 //     // let response: QueryResponse = deps.querier.query(&query).unwrap();
 
-    
-
-//        let x = 0; 
+//        let x = 0;
 
 //     }
 
 // }
 
-
-
 // //     #[test]
 // //     fn proper_initialization() {
 // //         let mut deps = mock_dependencies();